@@ -0,0 +1,207 @@
+//! Contains the AsyncReader for reading data from an avro datafile in an `AsyncRead` source.
+//! The header and the blocks are parsed with the same code as the Reader, once enough bytes are buffered.
+
+use crate::error::AvrowErr;
+use crate::reader::{decode_block, read_block, Header};
+use crate::resolution::ResolutionPlan;
+use crate::schema::Schema;
+use crate::value::Value;
+use futures_core::Stream;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+// Minimum number of bytes read from the source at a time.
+const READ_SIZE: usize = 8 * 1024;
+
+/// AsyncReader reads data from an avro datafile in an
+/// [`AsyncRead`](https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html) source.
+/// It is a [`Stream`](https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html) of values,
+/// which reads and decodes a block once all of it is buffered. Requires the `async` feature.
+/// ```rust
+/// use avrow::{AsyncReader, AsyncWriter, Schema};
+/// use futures::StreamExt;
+/// use std::str::FromStr;
+///
+/// futures::executor::block_on(async {
+///     let schema = Schema::from_str(r##""string""##).unwrap();
+///     let mut writer = AsyncWriter::new(&schema, vec![]).await.unwrap();
+///     writer.write("hello").await.unwrap();
+///     let buf = writer.into_inner().await.unwrap();
+///
+///     let mut reader = AsyncReader::new(buf.as_slice()).await.unwrap();
+///     while let Some(value) = reader.next().await {
+///         println!("{:?}", value.unwrap());
+///     }
+/// });
+/// ```
+pub struct AsyncReader<R> {
+    source: R,
+    header: Header,
+    // plan for resolving the writer's schema to the reader's schema, if one was given
+    plan: Option<ResolutionPlan>,
+    // bytes read from the source that are not parsed yet
+    buffer: Vec<u8>,
+    // values of the last decoded block
+    decoded: std::vec::IntoIter<Result<Value, AvrowErr>>,
+    end_of_source: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    /// Creates an AsyncReader, reading the header of the datafile from the source.
+    pub async fn new(source: R) -> Result<Self, AvrowErr> {
+        AsyncReader::open(source, None).await
+    }
+
+    /// Creates an AsyncReader with the given reader schema, reading the header of the datafile from the source.
+    /// Returns an error if data written with the writer schema in the header can never be read
+    /// with the reader schema.
+    pub async fn with_schema(source: R, reader_schema: &Schema) -> Result<Self, AvrowErr> {
+        AsyncReader::open(source, Some(reader_schema)).await
+    }
+
+    async fn open(mut source: R, reader_schema: Option<&Schema>) -> Result<Self, AvrowErr> {
+        let mut buffer = vec![];
+        let mut end_of_source = false;
+        let header = loop {
+            match parse(&buffer, |r| Header::from_reader(r))? {
+                Some((header, parsed)) => {
+                    buffer.drain(..parsed);
+                    break header;
+                }
+                None if end_of_source => return Err(AvrowErr::HeaderDecodeFailed),
+                None => {
+                    let read = std::future::poll_fn(|cx| {
+                        poll_read_more(Pin::new(&mut source), cx, &mut buffer)
+                    })
+                    .await
+                    .map_err(AvrowErr::DecodeFailed)?;
+                    end_of_source = read == 0;
+                }
+            }
+        };
+        let plan = match reader_schema {
+            Some(schema) => Some(ResolutionPlan::new(schema, &header.schema)?),
+            None => None,
+        };
+
+        Ok(AsyncReader {
+            source,
+            header,
+            plan,
+            buffer,
+            decoded: vec![].into_iter(),
+            end_of_source,
+        })
+    }
+
+    /// Retrieves a reference to the header metadata map.
+    pub fn meta(&self) -> &HashMap<String, Vec<u8>> {
+        self.header.metadata()
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncReader<R> {
+    type Item = Result<Value, AvrowErr>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(value) = this.decoded.next() {
+                return Poll::Ready(Some(value));
+            }
+            let sync_marker = this.header.sync_marker;
+            match parse(&this.buffer, |r| read_block(r, &sync_marker)) {
+                Ok(Some(((entries, compressed), parsed))) => {
+                    this.buffer.drain(..parsed);
+                    let header = &this.header;
+                    this.decoded = decode_block(
+                        entries,
+                        compressed,
+                        &header.codec,
+                        &header.schema,
+                        this.plan.as_ref(),
+                    )
+                    .into_iter();
+                }
+                // marks the end of the avro datafile
+                Ok(None) if this.end_of_source && this.buffer.is_empty() => {
+                    return Poll::Ready(None)
+                }
+                Ok(None) if this.end_of_source => {
+                    this.buffer.clear();
+                    return Poll::Ready(Some(Err(AvrowErr::DecodeFailed(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Unexpected end of data in the last block",
+                    )))));
+                }
+                Ok(None) => {
+                    match poll_read_more(Pin::new(&mut this.source), cx, &mut this.buffer) {
+                        Poll::Ready(Ok(read)) => this.end_of_source = read == 0,
+                        Poll::Ready(Err(e)) => {
+                            return Poll::Ready(Some(Err(AvrowErr::DecodeFailed(e))))
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Err(e) => {
+                    // the following blocks cannot be found without a valid sync marker
+                    this.buffer.clear();
+                    this.end_of_source = true;
+                    return Poll::Ready(Some(Err(AvrowErr::DecodeFailed(e))));
+                }
+            }
+        }
+    }
+}
+
+// Appends the bytes available from the source to the buffer, returning how many were read.
+fn poll_read_more<R: AsyncRead>(
+    source: Pin<&mut R>,
+    cx: &mut Context<'_>,
+    buffer: &mut Vec<u8>,
+) -> Poll<Result<usize, Error>> {
+    let len = buffer.len();
+    // reads at least as many bytes as are buffered, so that a large block is not parsed too often
+    buffer.resize(len + READ_SIZE.max(len), 0);
+    let mut read_buf = ReadBuf::new(&mut buffer[len..]);
+    let poll = source.poll_read(cx, &mut read_buf);
+    let read = read_buf.filled().len();
+    buffer.truncate(len + read);
+    poll.map_ok(|_| read)
+}
+
+// Parses from the start of the buffer with a parser of the sync Reader. Returns the parsed value with
+// the number of bytes parsed, or `None` if the parser ran out of bytes.
+fn parse<T, E, F>(buffer: &[u8], parser: F) -> Result<Option<(T, usize)>, E>
+where
+    F: FnOnce(&mut Buffered<'_>) -> Result<T, E>,
+{
+    let mut buffered = Buffered {
+        remaining: buffer,
+        exhausted: false,
+    };
+    match parser(&mut buffered) {
+        Ok(value) => Ok(Some((value, buffer.len() - buffered.remaining.len()))),
+        Err(_) if buffered.exhausted => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// A reader over the buffered bytes, which tracks if a read went past them.
+struct Buffered<'a> {
+    remaining: &'a [u8],
+    exhausted: bool,
+}
+
+impl<'a> Read for Buffered<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.remaining.read(buf)?;
+        if read < buf.len() {
+            self.exhausted = true;
+        }
+        Ok(read)
+    }
+}
@@ -0,0 +1,128 @@
+//! Contains the AsyncWriter for writing values to an avro datafile in an `AsyncWrite` sink.
+//! Headers and blocks are encoded with the same code as the Writer and then written asynchronously.
+
+use crate::codec::{BlockCompression, Codec};
+use crate::config::DEFAULT_FLUSH_INTERVAL;
+use crate::error::{AvrowErr, AvrowResult};
+use crate::schema::Schema;
+use crate::serde_avro;
+use crate::value::{EncodeOptions, Map, Value};
+use crate::writer::{encode_block, encode_header, encode_value, sync_marker};
+use serde::Serialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// AsyncWriter writes values to an avro datafile in an
+/// [`AsyncWrite`](https://docs.rs/tokio/1/tokio/io/trait.AsyncWrite.html) sink.
+/// Values are encoded to a buffer, which is written asynchronously as a block once it reaches
+/// the flush interval. Requires the `async` feature.
+pub struct AsyncWriter<'a, W> {
+    out_stream: W,
+    schema: &'a Schema,
+    block_stream: Vec<u8>,
+    block_count: usize,
+    codec: BlockCompression,
+    sync_marker: [u8; 16],
+    flush_interval: usize,
+}
+
+impl<'a, W: AsyncWrite + Unpin> AsyncWriter<'a, W> {
+    /// Creates a new `AsyncWriter`, writing the header of the datafile to the sink.
+    pub async fn new(schema: &'a Schema, out_stream: W) -> AvrowResult<AsyncWriter<'a, W>> {
+        AsyncWriter::with_codec(schema, out_stream, Codec::Null).await
+    }
+
+    /// Same as the `new` method, but additionally takes a `Codec` for compressing blocks.
+    /// Returns an error if the compression level of the codec is out of range.
+    pub async fn with_codec(
+        schema: &'a Schema,
+        out_stream: W,
+        codec: Codec,
+    ) -> AvrowResult<AsyncWriter<'a, W>> {
+        codec.validate()?;
+        let mut writer = AsyncWriter {
+            out_stream,
+            schema,
+            block_stream: Vec::with_capacity(DEFAULT_FLUSH_INTERVAL),
+            block_count: 0,
+            codec: BlockCompression::Builtin(codec),
+            sync_marker: sync_marker(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+        };
+        let mut header = vec![];
+        encode_header(
+            &mut header,
+            writer.schema,
+            &writer.codec,
+            Map::with_capacity(2),
+            &writer.sync_marker,
+        )?;
+        writer
+            .out_stream
+            .write_all(&header)
+            .await
+            .map_err(AvrowErr::EncodeFailed)?;
+        Ok(writer)
+    }
+
+    /// Set the flush interval (in bytes) for the internal buffer.
+    /// Defaults to [`DEFAULT_FLUSH_INTERVAL`](config/constant.DEFAULT_FLUSH_INTERVAL.html).
+    pub fn set_flush_interval(&mut self, interval: usize) {
+        self.flush_interval = interval;
+    }
+
+    /// Appends a value to the buffer, after validating it with the schema of this writer.
+    /// The buffer is written to the sink once it reaches the flush interval.
+    pub async fn write<T: Into<Value>>(&mut self, value: T) -> AvrowResult<()> {
+        encode_value(
+            value.into(),
+            self.schema,
+            &mut self.block_stream,
+            EncodeOptions::default(),
+        )?;
+        self.block_count += 1;
+
+        if self.block_stream.len() >= self.flush_interval {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Appends a native Rust value to the buffer. The value must implement Serde's `Serialize` trait.
+    pub async fn serialize<T: Serialize>(&mut self, value: T) -> AvrowResult<()> {
+        let value = serde_avro::to_value(&value)?;
+        self.write(value).await
+    }
+
+    /// Writes any buffered values to the sink as a block and flushes the sink.
+    pub async fn flush(&mut self) -> AvrowResult<()> {
+        // bail if no data is written or it has already been flushed before
+        if self.block_count == 0 {
+            return Ok(());
+        }
+        let mut block = vec![];
+        encode_block(
+            &mut block,
+            self.block_count,
+            &mut self.block_stream,
+            &self.codec,
+            &self.sync_marker,
+        )?;
+        self.out_stream
+            .write_all(&block)
+            .await
+            .map_err(AvrowErr::EncodeFailed)?;
+        self.out_stream
+            .flush()
+            .await
+            .map_err(AvrowErr::EncodeFailed)?;
+        self.block_count = 0;
+        self.block_stream.clear();
+        Ok(())
+    }
+
+    /// Consumes self and yields the inner `AsyncWrite` instance, after flushing any buffered values.
+    pub async fn into_inner(mut self) -> AvrowResult<W> {
+        self.flush().await?;
+        Ok(self.out_stream)
+    }
+}
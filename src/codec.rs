@@ -1,7 +1,11 @@
 use crate::error::AvrowErr;
 use crate::util::{encode_long, encode_raw_bytes};
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Debug;
 use std::io::Write;
+use std::sync::Arc;
 
 // Given a slice of bytes, generates a CRC for it
 #[cfg(feature = "snappy")]
@@ -30,21 +34,187 @@ pub(crate) fn compress_snappy(uncompressed_buffer: &[u8]) -> Result<Vec<u8>, Avr
 }
 
 #[cfg(feature = "deflate")]
-pub fn compress_deflate(uncompressed_buffer: &[u8]) -> Result<Vec<u8>, AvrowErr> {
+pub fn compress_deflate(level: u32, uncompressed_buffer: &[u8]) -> Result<Vec<u8>, AvrowErr> {
     use flate2::{write::DeflateEncoder, Compression};
 
-    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(level));
     encoder
-        .write(uncompressed_buffer)
+        .write_all(uncompressed_buffer)
         .map_err(AvrowErr::EncodeFailed)?;
     encoder.finish().map_err(AvrowErr::EncodeFailed)
 }
 
 #[cfg(feature = "zstd")]
-pub(crate) fn zstd_compress(level: i32, uncompressed_buffer: &[u8]) -> Result<Vec<u8>, AvrowErr> {
-    let comp = zstdd::encode_all(std::io::Cursor::new(uncompressed_buffer), level)
+use crate::value::Value;
+
+// Metadata keys in the datafile header for the options of the zstd codec that readers need to know about.
+#[cfg(feature = "zstd")]
+const ZSTD_DICTIONARY_KEY: &str = "avro.zstd.dictionary";
+#[cfg(feature = "zstd")]
+const ZSTD_DICTIONARY_ID_KEY: &str = "avro.zstd.dictionary.id";
+#[cfg(feature = "zstd")]
+const ZSTD_WINDOW_LOG_KEY: &str = "avro.zstd.window_log";
+
+// Options of the zstd codec, besides the compression level.
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ZstdOptions {
+    pub(crate) dictionary: Option<Arc<Vec<u8>>>,
+    // store the dictionary in the header instead of its id
+    pub(crate) embed_dictionary: bool,
+    pub(crate) long_distance_matching: bool,
+    pub(crate) window_log: Option<u32>,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdOptions {
+    pub(crate) fn is_default(&self) -> bool {
+        self.dictionary.is_none() && !self.long_distance_matching && self.window_log.is_none()
+    }
+
+    // Adds the metadata readers need for decompressing blocks to the header metadata.
+    pub(crate) fn write_metadata(
+        &self,
+        metadata: &mut HashMap<String, Value>,
+    ) -> Result<(), AvrowErr> {
+        if let Some(dictionary) = &self.dictionary {
+            if self.embed_dictionary {
+                metadata.insert(
+                    ZSTD_DICTIONARY_KEY.to_string(),
+                    Value::Bytes(dictionary.to_vec()),
+                );
+            } else {
+                let id = zstd_dictionary_id(dictionary).ok_or_else(|| {
+                    AvrowErr::CodecErr(
+                        "a zstd dictionary without an id must be embedded in the header"
+                            .to_string(),
+                    )
+                })?;
+                metadata.insert(
+                    ZSTD_DICTIONARY_ID_KEY.to_string(),
+                    Value::Bytes(id.to_string().into_bytes()),
+                );
+            }
+        }
+        if let Some(window_log) = self.window_log {
+            if !(10..=31).contains(&window_log) {
+                return Err(AvrowErr::CodecErr(format!(
+                    "zstd window log {} is not within 10 and 31",
+                    window_log
+                )));
+            }
+            metadata.insert(
+                ZSTD_WINDOW_LOG_KEY.to_string(),
+                Value::Bytes(window_log.to_string().into_bytes()),
+            );
+        }
+        Ok(())
+    }
+
+    // Reads the options from the header metadata, looking up dictionaries referenced by id in the registry.
+    fn from_metadata(
+        metadata: &HashMap<String, Vec<u8>>,
+        registry: &CodecRegistry,
+    ) -> Result<Self, AvrowErr> {
+        let parse = |key: &str| -> Result<Option<u32>, AvrowErr> {
+            metadata
+                .get(key)
+                .map(|v| {
+                    std::str::from_utf8(v)
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .ok_or_else(|| AvrowErr::CodecErr(format!("invalid value for {}", key)))
+                })
+                .transpose()
+        };
+        let dictionary = match (
+            metadata.get(ZSTD_DICTIONARY_KEY),
+            parse(ZSTD_DICTIONARY_ID_KEY)?,
+        ) {
+            (Some(dictionary), _) => Some(Arc::new(dictionary.clone())),
+            (None, Some(id)) => Some(
+                registry
+                    .zstd_dictionaries
+                    .get(&id)
+                    .cloned()
+                    .ok_or(AvrowErr::ZstdDictionaryNotFound(id))?,
+            ),
+            (None, None) => None,
+        };
+        Ok(ZstdOptions {
+            dictionary,
+            embed_dictionary: false,
+            long_distance_matching: false,
+            window_log: parse(ZSTD_WINDOW_LOG_KEY)?,
+        })
+    }
+}
+
+// Returns the id of a dictionary trained with zstd, or None for a raw content dictionary.
+#[cfg(feature = "zstd")]
+fn zstd_dictionary_id(dictionary: &[u8]) -> Option<u32> {
+    use byteorder::ByteOrder;
+
+    if dictionary.len() >= 8 && dictionary[..4] == [0x37, 0xA4, 0x30, 0xEC] {
+        Some(byteorder::LittleEndian::read_u32(&dictionary[4..8]))
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "zstd")]
+pub(crate) fn zstd_compress(
+    level: i32,
+    options: &ZstdOptions,
+    uncompressed_buffer: &[u8],
+) -> Result<Vec<u8>, AvrowErr> {
+    use zstdd::stream::raw::{CParameter, Encoder};
+    use zstdd::stream::zio::Writer;
+
+    let dictionary = options
+        .dictionary
+        .as_ref()
+        .map_or(&[][..], |d| d.as_slice());
+    let mut encoder =
+        Encoder::with_dictionary(level, dictionary).map_err(AvrowErr::EncodeFailed)?;
+    if options.long_distance_matching {
+        encoder
+            .set_parameter(CParameter::EnableLongDistanceMatching(true))
+            .map_err(AvrowErr::EncodeFailed)?;
+    }
+    if let Some(window_log) = options.window_log {
+        encoder
+            .set_parameter(CParameter::WindowLog(window_log))
+            .map_err(AvrowErr::EncodeFailed)?;
+    }
+    let mut writer = Writer::new(Vec::new(), encoder);
+    writer
+        .write_all(uncompressed_buffer)
         .map_err(AvrowErr::EncodeFailed)?;
-    Ok(comp)
+    writer.finish().map_err(AvrowErr::EncodeFailed)?;
+    Ok(writer.into_inner().0)
+}
+
+#[cfg(feature = "bzip2")]
+pub(crate) fn compress_bzip2(level: u32, uncompressed_buffer: &[u8]) -> Result<Vec<u8>, AvrowErr> {
+    use bzip2::{write::BzEncoder, Compression};
+
+    let mut encoder = BzEncoder::new(Vec::new(), Compression::new(level));
+    encoder
+        .write_all(uncompressed_buffer)
+        .map_err(AvrowErr::EncodeFailed)?;
+    encoder.finish().map_err(AvrowErr::EncodeFailed)
+}
+
+#[cfg(feature = "xz")]
+pub(crate) fn compress_xz(level: u32, uncompressed_buffer: &[u8]) -> Result<Vec<u8>, AvrowErr> {
+    use xz2::write::XzEncoder;
+
+    let mut encoder = XzEncoder::new(Vec::new(), level);
+    encoder
+        .write_all(uncompressed_buffer)
+        .map_err(AvrowErr::EncodeFailed)?;
+    encoder.finish().map_err(AvrowErr::EncodeFailed)
 }
 
 #[cfg(feature = "deflate")]
@@ -89,11 +259,29 @@ pub(crate) fn decompress_snappy(
 
 #[cfg(feature = "zstd")]
 pub(crate) fn decompress_zstd(
+    options: &ZstdOptions,
     compressed_buffer: &[u8],
     uncompressed: &mut Vec<u8>,
 ) -> Result<(), AvrowErr> {
-    let mut decoder = zstdd::Decoder::new(compressed_buffer).map_err(AvrowErr::DecodeFailed)?;
-    std::io::copy(&mut decoder, uncompressed).map_err(AvrowErr::DecodeFailed)?;
+    use std::io::Read;
+    use zstdd::stream::raw::{DParameter, Decoder};
+    use zstdd::stream::zio::Reader;
+
+    let dictionary = options
+        .dictionary
+        .as_ref()
+        .map_or(&[][..], |d| d.as_slice());
+    let mut decoder = Decoder::with_dictionary(dictionary).map_err(AvrowErr::DecodeFailed)?;
+    if let Some(window_log) = options.window_log {
+        decoder
+            .set_parameter(DParameter::WindowLogMax(window_log))
+            .map_err(AvrowErr::DecodeFailed)?;
+    }
+    let mut reader = Reader::new(compressed_buffer, decoder);
+    uncompressed.clear();
+    reader
+        .read_to_end(uncompressed)
+        .map_err(AvrowErr::DecodeFailed)?;
     Ok(())
 }
 
@@ -103,9 +291,13 @@ pub(crate) fn decompress_bzip2(
     uncompressed: &mut Vec<u8>,
 ) -> Result<(), AvrowErr> {
     use bzip2::read::BzDecoder;
-    let decompressor = BzDecoder::new(compressed_buffer);
-    let mut buf = decompressor.into_inner();
-    std::io::copy(&mut buf, uncompressed).map_err(AvrowErr::DecodeFailed)?;
+    use std::io::Read;
+
+    let mut decoder = BzDecoder::new(compressed_buffer);
+    uncompressed.clear();
+    decoder
+        .read_to_end(uncompressed)
+        .map_err(AvrowErr::DecodeFailed)?;
     Ok(())
 }
 
@@ -114,12 +306,17 @@ pub(crate) fn decompress_xz(
     compressed_buffer: &[u8],
     uncompressed: &mut Vec<u8>,
 ) -> Result<(), AvrowErr> {
+    use std::io::Read;
     use xz2::read::XzDecoder;
-    let decompressor = XzDecoder::new(compressed_buffer);
-    let mut buf = decompressor.into_inner();
-    std::io::copy(&mut buf, uncompressed).map_err(AvrowErr::DecodeFailed)?;
+
+    let mut decoder = XzDecoder::new(compressed_buffer);
+    uncompressed.clear();
+    decoder
+        .read_to_end(uncompressed)
+        .map_err(AvrowErr::DecodeFailed)?;
     Ok(())
 }
+
 /// Defines codecs one can use when writing avro data.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Codec {
@@ -127,19 +324,35 @@ pub enum Codec {
     Null,
     #[cfg(feature = "deflate")]
     /// The Deflate codec. <br>Uses https://docs.rs/flate2 as the underlying implementation.
-    Deflate,
+    Deflate {
+        /// Compression level from 0 (no compression) to 9 (best compression).
+        /// Defaults to [`DEFAULT_DEFLATE_LEVEL`](config/constant.DEFAULT_DEFLATE_LEVEL.html).
+        level: u32,
+    },
     #[cfg(feature = "snappy")]
     /// The Snappy codec. <br>Uses https://docs.rs/snap as the underlying implementation.
     Snappy,
     #[cfg(feature = "zstd")]
     /// The Zstd codec. <br>Uses https://docs.rs/zstd as the underlying implementation.
-    Zstd,
+    Zstd {
+        /// Compression level from 1 to 22, where 0 uses zstd's default level. Negative levels trade
+        /// compression for speed. Defaults to [`DEFAULT_ZSTD_LEVEL`](config/constant.DEFAULT_ZSTD_LEVEL.html).
+        level: i32,
+    },
     #[cfg(feature = "bzip2")]
     /// The Bzip2 codec. <br>Uses https://docs.rs/bzip2 as the underlying implementation.
-    Bzip2,
+    Bzip2 {
+        /// Compression level from 1 (fastest) to 9 (best compression).
+        /// Defaults to [`DEFAULT_BZIP2_LEVEL`](config/constant.DEFAULT_BZIP2_LEVEL.html).
+        level: u32,
+    },
     #[cfg(feature = "xz")]
     /// The Xz codec. <br>Uses https://docs.rs/crate/xz2 as the underlying implementation.
-    Xz,
+    Xz {
+        /// Compression level from 0 (fastest) to 9 (best compression).
+        /// Defaults to [`DEFAULT_XZ_LEVEL`](config/constant.DEFAULT_XZ_LEVEL.html).
+        level: u32,
+    },
 }
 
 impl AsRef<str> for Codec {
@@ -147,21 +360,40 @@ impl AsRef<str> for Codec {
         match self {
             Codec::Null => "null",
             #[cfg(feature = "deflate")]
-            Codec::Deflate => "deflate",
+            Codec::Deflate { .. } => "deflate",
             #[cfg(feature = "snappy")]
             Codec::Snappy => "snappy",
             #[cfg(feature = "zstd")]
-            Codec::Zstd => "zstd",
+            Codec::Zstd { .. } => "zstd",
             #[cfg(feature = "bzip2")]
-            Codec::Bzip2 => "bzip2",
+            Codec::Bzip2 { .. } => "bzip2",
             #[cfg(feature = "xz")]
-            Codec::Xz => "xz",
+            Codec::Xz { .. } => "xz",
         }
     }
 }
 
-// TODO allow all of these to be configurable for setting compression ratio/level
 impl Codec {
+    // Checks that the compression level is supported by the codec.
+    pub(crate) fn validate(&self) -> Result<(), AvrowErr> {
+        let valid = match *self {
+            #[cfg(feature = "deflate")]
+            Codec::Deflate { level } => level <= 9,
+            #[cfg(feature = "bzip2")]
+            Codec::Bzip2 { level } => (1..=9).contains(&level),
+            #[cfg(feature = "xz")]
+            Codec::Xz { level } => level <= 9,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd { level } => level <= 22,
+            _ => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(AvrowErr::InvalidCompressionLevel(format!("{:?}", self)))
+        }
+    }
+
     pub(crate) fn encode<W: Write>(
         &self,
         block_stream: &mut [u8],
@@ -191,37 +423,28 @@ impl Codec {
                     .map_err(AvrowErr::EncodeFailed)?;
             }
             #[cfg(feature = "deflate")]
-            Codec::Deflate => {
-                let compressed_data = compress_deflate(block_stream)?;
+            Codec::Deflate { level } => {
+                let compressed_data = compress_deflate(*level, block_stream)?;
                 encode_long(compressed_data.len() as i64, out_stream)?;
                 encode_raw_bytes(&*compressed_data, out_stream)?;
             }
             #[cfg(feature = "zstd")]
-            Codec::Zstd => {
-                let compressed_data = zstd_compress(0, block_stream)?;
+            Codec::Zstd { level } => {
+                let compressed_data = zstd_compress(*level, &ZstdOptions::default(), block_stream)?;
                 encode_long(compressed_data.len() as i64, out_stream)?;
                 encode_raw_bytes(&*compressed_data, out_stream)?;
             }
             #[cfg(feature = "bzip2")]
-            Codec::Bzip2 => {
-                use bzip2::read::BzEncoder;
-                use bzip2::Compression;
-                use std::io::Cursor;
-                let compressor = BzEncoder::new(Cursor::new(block_stream), Compression::new(5));
-                let vec = compressor.into_inner().into_inner();
-
-                encode_long(vec.len() as i64, out_stream)?;
-                encode_raw_bytes(&*vec, out_stream)?;
+            Codec::Bzip2 { level } => {
+                let compressed_data = compress_bzip2(*level, block_stream)?;
+                encode_long(compressed_data.len() as i64, out_stream)?;
+                encode_raw_bytes(&compressed_data, out_stream)?;
             }
             #[cfg(feature = "xz")]
-            Codec::Xz => {
-                use std::io::Cursor;
-                use xz2::read::XzEncoder;
-                let compressor = XzEncoder::new(Cursor::new(block_stream), 6);
-                let vec = compressor.into_inner().into_inner();
-
-                encode_long(vec.len() as i64, out_stream)?;
-                encode_raw_bytes(&*vec, out_stream)?;
+            Codec::Xz { level } => {
+                let compressed_data = compress_xz(*level, block_stream)?;
+                encode_long(compressed_data.len() as i64, out_stream)?;
+                encode_raw_bytes(&compressed_data, out_stream)?;
             }
         }
         Ok(())
@@ -240,13 +463,15 @@ impl Codec {
             #[cfg(feature = "snappy")]
             Codec::Snappy => decompress_snappy(&compressed, uncompressed.get_mut()),
             #[cfg(feature = "deflate")]
-            Codec::Deflate => decompress_deflate(&compressed, uncompressed.get_mut()),
+            Codec::Deflate { .. } => decompress_deflate(&compressed, uncompressed.get_mut()),
             #[cfg(feature = "zstd")]
-            Codec::Zstd => decompress_zstd(&compressed, uncompressed.get_mut()),
+            Codec::Zstd { .. } => {
+                decompress_zstd(&ZstdOptions::default(), &compressed, uncompressed.get_mut())
+            }
             #[cfg(feature = "bzip2")]
-            Codec::Bzip2 => decompress_bzip2(&compressed, uncompressed.get_mut()),
+            Codec::Bzip2 { .. } => decompress_bzip2(&compressed, uncompressed.get_mut()),
             #[cfg(feature = "xz")]
-            Codec::Xz => decompress_xz(&compressed, uncompressed.get_mut()),
+            Codec::Xz { .. } => decompress_xz(&compressed, uncompressed.get_mut()),
         }
     }
 }
@@ -260,14 +485,181 @@ impl std::convert::TryFrom<&str> for Codec {
             #[cfg(feature = "snappy")]
             "snappy" => Ok(Codec::Snappy),
             #[cfg(feature = "deflate")]
-            "deflate" => Ok(Codec::Deflate),
+            "deflate" => Ok(Codec::Deflate {
+                level: crate::config::DEFAULT_DEFLATE_LEVEL,
+            }),
             #[cfg(feature = "zstd")]
-            "zstd" => Ok(Codec::Zstd),
+            "zstd" => Ok(Codec::Zstd {
+                level: crate::config::DEFAULT_ZSTD_LEVEL,
+            }),
             #[cfg(feature = "bzip2")]
-            "bzip2" => Ok(Codec::Bzip2),
+            "bzip2" => Ok(Codec::Bzip2 {
+                level: crate::config::DEFAULT_BZIP2_LEVEL,
+            }),
             #[cfg(feature = "xz")]
-            "xz" => Ok(Codec::Xz),
+            "xz" => Ok(Codec::Xz {
+                level: crate::config::DEFAULT_XZ_LEVEL,
+            }),
             o => Err(AvrowErr::UnsupportedCodec(o.to_string())),
         }
     }
 }
+
+/// A user defined codec for compressing the blocks of a datafile. User defined codecs are registered in a
+/// [CodecRegistry](struct.CodecRegistry.html), which is passed to the [WriterBuilder](struct.WriterBuilder.html)
+/// for writing and to the [ReaderBuilder](struct.ReaderBuilder.html) for reading datafiles.
+/// ```rust
+/// use avrow::{AvrowErr, BlockCodec};
+///
+/// // Flips the bits of every byte, which is not a compression but shows the idea.
+/// #[derive(Debug)]
+/// struct Flip;
+///
+/// impl BlockCodec for Flip {
+///     fn name(&self) -> &str {
+///         "flip"
+///     }
+///
+///     fn compress(&self, block: &[u8]) -> Result<Vec<u8>, AvrowErr> {
+///         Ok(block.iter().map(|b| !b).collect())
+///     }
+///
+///     fn decompress(&self, compressed: &[u8], uncompressed: &mut Vec<u8>) -> Result<(), AvrowErr> {
+///         uncompressed.extend(compressed.iter().map(|b| !b));
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait BlockCodec: Debug + Send + Sync {
+    /// The name of the codec, as it appears in the `avro.codec` metadata of a datafile header.
+    fn name(&self) -> &str;
+
+    /// Compresses a block of encoded values.
+    fn compress(&self, block: &[u8]) -> Result<Vec<u8>, AvrowErr>;
+
+    /// Decompresses a block written with this codec, appending the encoded values to `uncompressed`,
+    /// which is empty.
+    fn decompress(&self, compressed: &[u8], uncompressed: &mut Vec<u8>) -> Result<(), AvrowErr>;
+}
+
+/// A registry of user defined codecs by their names. When reading, codecs in a datafile header that are not
+/// built in are looked up in the registry. It also stores the zstd dictionaries that datafiles refer to by id.
+#[derive(Debug, Clone, Default)]
+pub struct CodecRegistry {
+    codecs: HashMap<String, Arc<dyn BlockCodec>>,
+    #[cfg(feature = "zstd")]
+    zstd_dictionaries: HashMap<u32, Arc<Vec<u8>>>,
+}
+
+impl CodecRegistry {
+    /// Creates an empty codec registry.
+    pub fn new() -> Self {
+        CodecRegistry::default()
+    }
+
+    /// Registers a codec by its name, replacing any codec registered before with the same name.
+    pub fn register<C: BlockCodec + 'static>(&mut self, codec: C) {
+        self.codecs
+            .insert(codec.name().to_string(), Arc::new(codec));
+    }
+
+    /// Adds a zstd dictionary trained with zstd, for reading datafiles that refer to it by its id.
+    /// Returns the id of the dictionary, or an error if it is a raw content dictionary without an id.
+    #[cfg(feature = "zstd")]
+    pub fn register_zstd_dictionary(&mut self, dictionary: Vec<u8>) -> Result<u32, AvrowErr> {
+        let id = zstd_dictionary_id(&dictionary)
+            .ok_or_else(|| AvrowErr::CodecErr("zstd dictionary does not have an id".to_string()))?;
+        self.zstd_dictionaries.insert(id, Arc::new(dictionary));
+        Ok(id)
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Arc<dyn BlockCodec>> {
+        self.codecs.get(name)
+    }
+}
+
+// The codec used for the blocks of a datafile, which is either a built in or a user defined codec.
+#[derive(Debug, Clone)]
+pub(crate) enum BlockCompression {
+    Builtin(Codec),
+    // zstd with a dictionary or other options besides the level
+    #[cfg(feature = "zstd")]
+    Zstd(i32, ZstdOptions),
+    Custom(Arc<dyn BlockCodec>),
+}
+
+impl BlockCompression {
+    // Resolves the codec of a datafile from its header metadata, looking up codecs that are not built in
+    // in the registry.
+    #[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+    pub(crate) fn from_metadata(
+        name: &str,
+        metadata: &HashMap<String, Vec<u8>>,
+        registry: &CodecRegistry,
+    ) -> Result<Self, AvrowErr> {
+        match Codec::try_from(name) {
+            #[cfg(feature = "zstd")]
+            Ok(Codec::Zstd { level }) => {
+                let options = ZstdOptions::from_metadata(metadata, registry)?;
+                if options.is_default() {
+                    Ok(BlockCompression::Builtin(Codec::Zstd { level }))
+                } else {
+                    Ok(BlockCompression::Zstd(level, options))
+                }
+            }
+            Ok(codec) => Ok(BlockCompression::Builtin(codec)),
+            Err(e) => registry
+                .get(name)
+                .map(|codec| BlockCompression::Custom(codec.clone()))
+                .ok_or(e),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            BlockCompression::Builtin(codec) => codec.as_ref(),
+            #[cfg(feature = "zstd")]
+            BlockCompression::Zstd(..) => "zstd",
+            BlockCompression::Custom(codec) => codec.name(),
+        }
+    }
+
+    pub(crate) fn encode<W: Write>(
+        &self,
+        block_stream: &mut [u8],
+        out_stream: &mut W,
+    ) -> Result<(), AvrowErr> {
+        match self {
+            BlockCompression::Builtin(codec) => codec.encode(block_stream, out_stream),
+            #[cfg(feature = "zstd")]
+            BlockCompression::Zstd(level, options) => {
+                let compressed_data = zstd_compress(*level, options, block_stream)?;
+                encode_long(compressed_data.len() as i64, out_stream)?;
+                encode_raw_bytes(&compressed_data, out_stream)
+            }
+            BlockCompression::Custom(codec) => {
+                let compressed_data = codec.compress(block_stream)?;
+                encode_long(compressed_data.len() as i64, out_stream)?;
+                encode_raw_bytes(&compressed_data, out_stream)
+            }
+        }
+    }
+
+    pub(crate) fn decode(
+        &self,
+        compressed: Vec<u8>,
+        uncompressed: &mut std::io::Cursor<Vec<u8>>,
+    ) -> Result<(), AvrowErr> {
+        match self {
+            BlockCompression::Builtin(codec) => codec.decode(compressed, uncompressed),
+            #[cfg(feature = "zstd")]
+            BlockCompression::Zstd(_, options) => {
+                decompress_zstd(options, &compressed, uncompressed.get_mut())
+            }
+            BlockCompression::Custom(codec) => {
+                uncompressed.get_mut().clear();
+                codec.decompress(&compressed, uncompressed.get_mut())
+            }
+        }
+    }
+}
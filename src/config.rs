@@ -13,3 +13,15 @@ pub const BLOCK_SIZE: usize = 4096;
 /// is flushed/synced to the main buffer. Suggested values are between 2K (bytes) and 2M
 // TODO make this configurable
 pub const DEFAULT_FLUSH_INTERVAL: usize = 16 * BLOCK_SIZE;
+/// Default compression level of the deflate codec.
+#[cfg(feature = "deflate")]
+pub const DEFAULT_DEFLATE_LEVEL: u32 = 6;
+/// Default compression level of the zstd codec.
+#[cfg(feature = "zstd")]
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+/// Default compression level of the bzip2 codec.
+#[cfg(feature = "bzip2")]
+pub const DEFAULT_BZIP2_LEVEL: u32 = 5;
+/// Default compression level of the xz codec.
+#[cfg(feature = "xz")]
+pub const DEFAULT_XZ_LEVEL: u32 = 6;
@@ -47,6 +47,8 @@ pub enum AvrowErr {
     NoImmediateUnion,
     #[error("Failed building the Writer")]
     WriterBuildFailed,
+    #[error("Failed building the Reader")]
+    ReaderBuildFailed,
     #[error("Json must be an object for record")]
     ExpectedJsonObject,
 
@@ -57,6 +59,8 @@ pub enum AvrowErr {
     HeaderDecodeFailed,
     #[error("Unsupported codec {0}, did you enable the feature?")]
     UnsupportedCodec(String),
+    #[error("Compression level is out of range for codec {0}")]
+    InvalidCompressionLevel(String),
     #[error("Named schema was not found in schema registry")]
     NamedSchemaNotFound,
     #[error("Schema resolution failed. reader's schema {0} != writer's schema {1}")]
@@ -71,20 +75,36 @@ pub enum AvrowErr {
     UnionSchemaMismatch,
     #[error("Map's value schema do not match")]
     MapSchemaMismatch,
-    #[error("Fixed schema names do not match")]
-    FixedSchemaNameMismatch,
-    #[error("Could not find symbol at index {idx} in reader schema")]
-    EnumSymbolNotFound { idx: usize },
-    #[error("Reader's enum name does not match writer's enum name")]
-    EnumNameMismatch,
-    #[error("Readers' record name does not match writer's record name")]
-    RecordNameMismatch,
+    #[error("Reader's fixed name does not match writer's fixed name {writer}, tried: {tried:?}")]
+    FixedSchemaNameMismatch { writer: String, tried: Vec<String> },
+    #[error(
+        "Writer's symbol {symbol} not found in reader's enum symbols and reader has no default"
+    )]
+    EnumSymbolNotFound { symbol: String },
+    #[error("Reader's enum name does not match writer's enum name {writer}, tried: {tried:?}")]
+    EnumNameMismatch { writer: String, tried: Vec<String> },
+    #[error("Readers' record name does not match writer's record name {writer}, tried: {tried:?}")]
+    RecordNameMismatch { writer: String, tried: Vec<String> },
+    #[error("Reader's field has no default and is not in writer's record, tried: {tried:?}")]
+    ReaderFieldNotInWriter { tried: Vec<String> },
     #[error("Array items schema does not match")]
     ArrayItemsMismatch,
     #[error("Snappy decoder failed to get length of decompressed buffer")]
     SnappyDecompressLenFailed,
     #[error("End of file reached")]
     Eof,
+    #[error("Expected single object marker: `0xC3 0x01`")]
+    InvalidSingleObjectMarker,
+    #[error("Expected wire format magic byte 0, found: {0}")]
+    InvalidWireFormatMagic(u8),
+    #[error("Schema not found in schema store: {0}")]
+    SchemaNotInStore(String),
+    #[error("Schema store failed reading or writing schemas")]
+    SchemaStoreIo(#[source] std::io::Error),
+    #[error("Schema store lock is poisoned")]
+    SchemaStorePoisoned,
+    #[error("Schema registry request failed: {0}")]
+    SchemaRegistryErr(String),
 
     // Schema parse errors
     #[error("Failed to parse avro schema")]
@@ -97,6 +117,10 @@ pub enum AvrowErr {
     SchemaDataValidationFailed(String, String),
     #[error("Schema has a field not found in the value")]
     RecordFieldMissing,
+    #[error("Record `{record}` has no value for field `{field}` and the field has no default")]
+    MissingRecordField { record: String, field: String },
+    #[error("Record `{record}` has a field `{field}` that is not in its schema")]
+    UnknownRecordField { record: String, field: String },
     #[error("Record schema does not a have a required field named `name`")]
     RecordNameNotFound,
     #[error("Record schema does not a have a required field named `type`")]
@@ -143,10 +167,26 @@ pub enum AvrowErr {
     InvalidType(String),
     #[error("Enum schema parsing failed, found: {0}")]
     EnumParseErr(String),
+    #[error("Enum default must be one of the enum's symbols, found: {0}")]
+    InvalidEnumDefault(String),
     #[error("Primitve schema must be a string")]
     InvalidPrimitiveSchema,
+    #[error("Invalid decimal schema: {0}")]
+    InvalidDecimalSchema(String),
+    #[error("Failed to parse avro protocol: {0}")]
+    ProtocolParseErr(String),
+    #[error("Message `{0}` not found in protocol")]
+    MessageNotFound(String),
+    #[error("Message `{message}` expects {expected} request parameters, found {found}")]
+    RequestParamCount {
+        message: String,
+        expected: usize,
+        found: usize,
+    },
 
     // Validation errors
+    #[error("Value does not match the schema: {}", .0.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; "))]
+    ValidationFailed(Vec<crate::schema::Violation>),
     #[error("Mismatch in fixed bytes length: {found}, {expected}")]
     FixedValueLenMismatch { found: usize, expected: usize },
     #[error("namespaces must either be empty or follow the grammer <name>[(<dot><name>)*")]
@@ -165,6 +205,26 @@ pub enum AvrowErr {
     NamedSchemaNotFoundForValue,
     #[error("Value schema not found in union")]
     NotFoundInUnion,
+    #[error("Decimal has {found} digits, schema allows a precision of {precision}")]
+    DecimalPrecisionExceeded { found: usize, precision: usize },
+    #[error("Decimal scale {found} cannot be converted to schema scale {expected} without loss")]
+    DecimalScaleMismatch { found: usize, expected: usize },
+    #[error("Invalid uuid: {0}")]
+    InvalidUuid(String),
+    #[error("Logical type error: {0}")]
+    LogicalTypeErr(String),
+    #[error("Codec error: {0}")]
+    CodecErr(String),
+    #[error("Zstd dictionary with id {0} not found in the codec registry")]
+    ZstdDictionaryNotFound(u32),
+    #[error("A worker thread stopped unexpectedly")]
+    WorkerStopped,
+    #[error("I/O error: {0}")]
+    IoErr(#[from] std::io::Error),
+    #[error("Frame of {0} bytes exceeds the maximum frame length of {1} bytes")]
+    FrameTooLong(usize, usize),
+    #[error("Single object message has fingerprint {0}, expected {1}")]
+    FingerprintMismatch(i64, i64),
 
     // Serde specific errors
     #[error("Serde error: {0}")]
@@ -181,4 +241,10 @@ pub enum AvrowErr {
     // Value errors
     #[error("Expected value not found in variant instance")]
     ExpectedVariantNotFound,
+    #[error("Failed to parse decimal from: {0}")]
+    DecimalParseErr(String),
+    #[error("Failed to parse {0} from: {1}")]
+    TemporalParseErr(String, String),
+    #[error("Temporal value is out of range: {0}")]
+    TemporalOutOfRange(String),
 }
@@ -0,0 +1,300 @@
+//! Contains the DatumCodec for framing avro datums on byte streams with tokio's
+//! [`Framed`](https://docs.rs/tokio-util/0.7/tokio_util/codec/struct.Framed.html) and friends.
+
+use crate::error::AvrowErr;
+use crate::message::{read_single_object_header, to_single_object};
+use crate::reader::decode;
+use crate::schema::Schema;
+use crate::value::Value;
+use bytes::{Buf, BufMut, BytesMut};
+use std::io::{Cursor, Error, ErrorKind};
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Default maximum length (in bytes) of a frame's payload.
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+// Maximum length of a buffer in the avro rpc framing written by the encoder.
+const RPC_BUFFER_LENGTH: usize = 8 * 1024;
+
+/// How datums are delimited on the stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framing {
+    /// Each datum is prefixed with its length as a 4 byte big-endian integer.
+    LengthDelimited,
+    /// The [avro rpc framing](https://avro.apache.org/docs/current/spec.html#Message+Framing):
+    /// a datum is split into buffers, each prefixed with its length as a 4 byte big-endian
+    /// integer, and is terminated by an empty buffer.
+    Rpc,
+}
+
+/// DatumCodec is a tokio [`Encoder`](https://docs.rs/tokio-util/0.7/tokio_util/codec/trait.Encoder.html) and
+/// [`Decoder`](https://docs.rs/tokio-util/0.7/tokio_util/codec/trait.Decoder.html) of values, which are encoded
+/// with a fixed writer schema either as plain datums or as single object encoded messages.
+/// Requires the `tokio-codec` feature.
+/// ```rust
+/// use avrow::{DatumCodec, Framing, Schema, Value};
+/// use bytes::BytesMut;
+/// use std::str::FromStr;
+/// use std::sync::Arc;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let schema = Arc::new(Schema::from_str(r##""string""##).unwrap());
+/// let mut codec = DatumCodec::new(schema).set_framing(Framing::Rpc);
+/// let mut buf = BytesMut::new();
+/// codec.encode(Value::from("hello"), &mut buf).unwrap();
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some(Value::from("hello")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatumCodec {
+    schema: Arc<Schema>,
+    framing: Framing,
+    single_object: bool,
+    max_frame_length: usize,
+}
+
+impl DatumCodec {
+    /// Creates a DatumCodec for plain datums of the given schema, which are length delimited.
+    pub fn new(schema: Arc<Schema>) -> Self {
+        DatumCodec {
+            schema,
+            framing: Framing::LengthDelimited,
+            single_object: false,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+
+    /// Set how datums are delimited on the stream. Defaults to `Framing::LengthDelimited`.
+    pub fn set_framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Encode values as [single object encoded](https://avro.apache.org/docs/current/spec.html#single_object_encoding)
+    /// messages instead of plain datums. When decoding, the fingerprint of a message must match the schema
+    /// of this codec. Defaults to false.
+    pub fn set_single_object(mut self, single_object: bool) -> Self {
+        self.single_object = single_object;
+        self
+    }
+
+    /// Set the maximum length (in bytes) of a frame's payload. Longer frames fail to encode or decode.
+    /// Defaults to [`DEFAULT_MAX_FRAME_LENGTH`](constant.DEFAULT_MAX_FRAME_LENGTH.html).
+    pub fn set_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    fn check_length(&self, len: usize) -> Result<(), AvrowErr> {
+        if len > self.max_frame_length {
+            return Err(AvrowErr::FrameTooLong(len, self.max_frame_length));
+        }
+        Ok(())
+    }
+
+    // Returns the payload of the next frame, if all of it is buffered.
+    fn next_payload(&self, src: &mut BytesMut) -> Result<Option<Vec<u8>>, AvrowErr> {
+        match self.framing {
+            Framing::LengthDelimited => {
+                if src.len() < 4 {
+                    return Ok(None);
+                }
+                let len = read_length(src, 0);
+                self.check_length(len)?;
+                if src.len() < 4 + len {
+                    src.reserve(4 + len - src.len());
+                    return Ok(None);
+                }
+                src.advance(4);
+                Ok(Some(src.split_to(len).to_vec()))
+            }
+            Framing::Rpc => {
+                // finds the empty buffer that ends the frame before consuming anything
+                let mut offset = 0;
+                let mut payload_len = 0;
+                loop {
+                    if src.len() < offset + 4 {
+                        return Ok(None);
+                    }
+                    let len = read_length(src, offset);
+                    offset += 4;
+                    if len == 0 {
+                        break;
+                    }
+                    payload_len += len;
+                    self.check_length(payload_len)?;
+                    offset += len;
+                }
+                let frame = src.split_to(offset);
+                let mut payload = Vec::with_capacity(payload_len);
+                let mut buffers = &frame[..];
+                while buffers.len() > 4 {
+                    let len = read_length(buffers, 0);
+                    payload.extend_from_slice(&buffers[4..4 + len]);
+                    buffers = &buffers[4 + len..];
+                }
+                Ok(Some(payload))
+            }
+        }
+    }
+}
+
+impl<T: Into<Value>> Encoder<T> for DatumCodec {
+    type Error = AvrowErr;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), AvrowErr> {
+        let value = item.into();
+        let payload = if self.single_object {
+            to_single_object(value, &self.schema)?
+        } else {
+            self.schema.validate(&value)?;
+            let mut buf = vec![];
+            value.encode(&mut buf, self.schema.variant(), &self.schema.cxt)?;
+            buf
+        };
+        self.check_length(payload.len())?;
+
+        match self.framing {
+            Framing::LengthDelimited => {
+                dst.reserve(4 + payload.len());
+                dst.put_u32(payload.len() as u32);
+                dst.put_slice(&payload);
+            }
+            Framing::Rpc => {
+                let buffers = payload.chunks(RPC_BUFFER_LENGTH).len();
+                dst.reserve(payload.len() + 4 * (buffers + 1));
+                for buffer in payload.chunks(RPC_BUFFER_LENGTH) {
+                    dst.put_u32(buffer.len() as u32);
+                    dst.put_slice(buffer);
+                }
+                dst.put_u32(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for DatumCodec {
+    type Item = Value;
+    type Error = AvrowErr;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Value>, AvrowErr> {
+        let payload = match self.next_payload(src)? {
+            Some(payload) => payload,
+            None => return Ok(None),
+        };
+
+        let mut reader = Cursor::new(payload.as_slice());
+        if self.single_object {
+            let fingerprint = read_single_object_header(&mut reader)?;
+            let expected = self.schema.canonical_form().rabin64();
+            if fingerprint != expected {
+                return Err(AvrowErr::FingerprintMismatch(fingerprint, expected));
+            }
+        }
+        let value = decode(self.schema.variant(), &mut reader, &self.schema.cxt)?;
+        if reader.position() != payload.len() as u64 {
+            return Err(AvrowErr::DecodeFailed(Error::new(
+                ErrorKind::InvalidData,
+                "Unexpected trailing bytes after the datum in a frame",
+            )));
+        }
+        Ok(Some(value))
+    }
+}
+
+// Reads a 4 byte big-endian length at the offset.
+fn read_length(buf: &[u8], offset: usize) -> usize {
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&buf[offset..offset + 4]);
+    u32::from_be_bytes(len) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DatumCodec, Framing};
+    use crate::error::AvrowErr;
+    use crate::message::to_single_object;
+    use crate::schema::Schema;
+    use crate::value::Value;
+    use bytes::BytesMut;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::from_str(r##"{"type": "array", "items": "long"}"##).unwrap())
+    }
+
+    #[test]
+    fn round_trips_across_framings() {
+        for &framing in &[Framing::LengthDelimited, Framing::Rpc] {
+            for &single_object in &[false, true] {
+                let mut codec = DatumCodec::new(schema())
+                    .set_framing(framing)
+                    .set_single_object(single_object);
+                let small = Value::Array(vec![Value::Long(1), Value::Long(-2)]);
+                // spans several rpc buffers
+                let large = Value::Array((0..10_000).map(Value::Long).collect());
+                let mut buf = BytesMut::new();
+                codec.encode(small.clone(), &mut buf).unwrap();
+                codec.encode(large.clone(), &mut buf).unwrap();
+
+                // bytes arrive one at a time
+                let mut src = BytesMut::new();
+                let mut decoded = vec![];
+                for byte in buf.iter() {
+                    src.extend_from_slice(&[*byte]);
+                    if let Some(value) = codec.decode(&mut src).unwrap() {
+                        decoded.push(value);
+                    }
+                }
+                assert_eq!(decoded, vec![small.clone(), large]);
+                assert!(src.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn rpc_frame_ends_with_empty_buffer() {
+        let mut codec = DatumCodec::new(schema()).set_framing(Framing::Rpc);
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Value::Array(vec![Value::Long(1)]), &mut buf)
+            .unwrap();
+        assert_eq!(&buf[..], &[0, 0, 0, 3, 2, 2, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rejects_invalid_frames() {
+        let mut codec = DatumCodec::new(schema()).set_max_frame_length(4);
+        let mut buf = BytesMut::new();
+        assert!(codec.encode(Value::Long(1), &mut buf).is_err());
+        let long = Value::Array((0..10).map(Value::Long).collect());
+        assert!(matches!(
+            codec.encode(long, &mut buf),
+            Err(AvrowErr::FrameTooLong(_, 4))
+        ));
+        // the length is checked before the rest of the frame arrives
+        let mut src = BytesMut::from(&[0, 0, 1, 0][..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(AvrowErr::FrameTooLong(256, 4))
+        ));
+
+        // trailing bytes after the datum
+        let mut src = BytesMut::from(&[0, 0, 0, 2, 0, 0][..]);
+        assert!(codec.decode(&mut src).is_err());
+
+        // a message of another schema
+        let mut codec = DatumCodec::new(schema()).set_single_object(true);
+        let other = Schema::from_str(r##""long""##).unwrap();
+        let msg = to_single_object(1i64, &other).unwrap();
+        let mut src = BytesMut::new();
+        src.extend_from_slice(&(msg.len() as u32).to_be_bytes());
+        src.extend_from_slice(&msg);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(AvrowErr::FingerprintMismatch(_, _))
+        ));
+    }
+}
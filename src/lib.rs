@@ -61,24 +61,53 @@
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
 
+#[cfg(feature = "async")]
+mod async_reader;
+#[cfg(feature = "async")]
+mod async_writer;
 mod codec;
 pub mod config;
 mod error;
+#[cfg(feature = "tokio-codec")]
+mod framing;
+mod logical;
+mod message;
+mod pool;
+mod protocol;
 mod reader;
+#[cfg(feature = "registry")]
+mod registry;
+mod resolution;
 mod schema;
 mod serde_avro;
+mod store;
 mod util;
 mod value;
 mod writer;
 
-pub use codec::Codec;
+#[cfg(feature = "async")]
+pub use async_reader::AsyncReader;
+#[cfg(feature = "async")]
+pub use async_writer::AsyncWriter;
+pub use codec::{BlockCodec, Codec, CodecRegistry};
 pub use error::AvrowErr;
+#[cfg(feature = "tokio-codec")]
+pub use framing::{DatumCodec, Framing, DEFAULT_MAX_FRAME_LENGTH};
+pub use logical::Decimal;
+pub use logical::LogicalType;
+pub use message::{from_single_object, from_wire_format, to_single_object, to_wire_format};
+pub use protocol::{Message, Protocol};
 pub use reader::from_value;
 pub use reader::Header;
 pub use reader::Reader;
-pub use schema::Schema;
+pub use reader::ReaderBuilder;
+#[cfg(feature = "registry")]
+pub use registry::SchemaRegistryClient;
+pub use schema::{CompatibilityMode, Incompatibility, Schema, Violation};
 pub use serde_avro::to_value;
+pub use store::{DirectorySchemaStore, InMemorySchemaStore, SchemaStore};
 pub use value::Record;
+pub use value::UnionBranch;
 pub use value::Value;
 pub use writer::Writer;
 pub use writer::WriterBuilder;
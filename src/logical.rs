@@ -0,0 +1,702 @@
+//! Contains types for the avro [logical types](https://avro.apache.org/docs/current/spec.html#Logical+Types).
+//! A logical type annotates an underlying avro type with additional meaning, for example
+//! a `decimal` is encoded as `bytes` or `fixed` holding the unscaled value of the number.
+
+use crate::error::AvrowErr;
+use crate::schema::Variant;
+use crate::value::Value;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use num_bigint::{BigInt, Sign};
+use num_traits::{pow, Signed, Zero};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value as JsonValue};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use std::sync::Arc;
+
+// Name used to recognize a serialized `Decimal` in the serde serializer.
+pub(crate) const DECIMAL_NEWTYPE: &str = "$avrow::Decimal";
+
+/// An arbitrary precision decimal number backing the
+/// [decimal](https://avro.apache.org/docs/current/spec.html#Decimal) logical type.
+/// It is represented as an unscaled integer and a scale, such that the value of the
+/// decimal is `unscaled × 10^-scale`.
+///
+/// ```rust
+/// use avrow::Decimal;
+/// use std::str::FromStr;
+///
+/// let price = Decimal::new(1234, 2);
+/// assert_eq!(price, Decimal::from_str("12.34").unwrap());
+/// assert_eq!(price.to_string(), "12.34");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    unscaled: BigInt,
+    scale: usize,
+}
+
+impl Decimal {
+    /// Creates a decimal from its unscaled value and a scale.
+    pub fn new<T: Into<BigInt>>(unscaled: T, scale: usize) -> Self {
+        Decimal {
+            unscaled: unscaled.into(),
+            scale,
+        }
+    }
+
+    /// Creates a decimal from the two's-complement big-endian representation of its unscaled value.
+    /// This is the representation used by avro for encoding decimals.
+    pub fn from_be_bytes(bytes: &[u8], scale: usize) -> Self {
+        Decimal {
+            unscaled: BigInt::from_signed_bytes_be(bytes),
+            scale,
+        }
+    }
+
+    /// Returns the unscaled value as two's-complement big-endian bytes.
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.unscaled.to_signed_bytes_be()
+    }
+
+    /// Returns the unscaled integer value of this decimal.
+    pub fn unscaled(&self) -> &BigInt {
+        &self.unscaled
+    }
+
+    /// Returns the scale, i.e., the number of digits to the right of the decimal point.
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    /// Returns the number of significant digits in the unscaled value.
+    pub fn precision(&self) -> usize {
+        self.unscaled.abs().to_string().len()
+    }
+
+    // Returns an equal decimal with the given scale. Fails if the conversion would lose digits.
+    pub(crate) fn rescale(&self, scale: usize) -> Result<Decimal, AvrowErr> {
+        match self.scale.cmp(&scale) {
+            Ordering::Equal => Ok(self.clone()),
+            Ordering::Less => {
+                let factor = pow(BigInt::from(10), scale - self.scale);
+                Ok(Decimal::new(&self.unscaled * factor, scale))
+            }
+            Ordering::Greater => {
+                let factor = pow(BigInt::from(10), self.scale - scale);
+                if (&self.unscaled % &factor).is_zero() {
+                    Ok(Decimal::new(&self.unscaled / factor, scale))
+                } else {
+                    Err(AvrowErr::DecimalScaleMismatch {
+                        found: self.scale,
+                        expected: scale,
+                    })
+                }
+            }
+        }
+    }
+
+    // Returns the bytes to encode for a decimal schema with the given precision and scale.
+    pub(crate) fn to_schema_bytes(
+        &self,
+        precision: usize,
+        scale: usize,
+    ) -> Result<Vec<u8>, AvrowErr> {
+        let decimal = self.rescale(scale)?;
+        let found = decimal.precision();
+        if found > precision {
+            return Err(AvrowErr::DecimalPrecisionExceeded { found, precision });
+        }
+        Ok(decimal.to_be_bytes())
+    }
+}
+
+// Sign extends two's-complement big-endian bytes to fill a fixed of the given size.
+pub(crate) fn sign_extend(bytes: &[u8], size: usize) -> Result<Vec<u8>, AvrowErr> {
+    if bytes.len() > size {
+        return Err(AvrowErr::FixedValueLenMismatch {
+            found: bytes.len(),
+            expected: size,
+        });
+    }
+    let pad = match bytes.first() {
+        Some(b) if b & 0x80 != 0 => 0xff,
+        _ => 0x00,
+    };
+    let mut extended = vec![pad; size - bytes.len()];
+    extended.extend_from_slice(bytes);
+    Ok(extended)
+}
+
+// Returns the maximum number of digits that can be stored in a fixed of `size` bytes.
+pub(crate) fn max_fixed_precision(size: usize) -> usize {
+    if size == 0 {
+        return 0;
+    }
+    ((8 * size - 1) as f64 * 2f64.log10()).floor() as usize
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.unscaled.sign() == Sign::Minus {
+            "-"
+        } else {
+            ""
+        };
+        let digits = self.unscaled.abs().to_string();
+        if self.scale == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else if digits.len() > self.scale {
+            let (int, frac) = digits.split_at(digits.len() - self.scale);
+            write!(f, "{}{}.{}", sign, int, frac)
+        } else {
+            let zeros = "0".repeat(self.scale - digits.len());
+            write!(f, "{}0.{}{}", sign, zeros, digits)
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = AvrowErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || AvrowErr::DecimalParseErr(s.to_string());
+        let trimmed = s.trim();
+        let (negative, unsigned) = match trimmed.as_bytes().first() {
+            Some(b'-') => (true, &trimmed[1..]),
+            Some(b'+') => (false, &trimmed[1..]),
+            _ => (false, trimmed),
+        };
+        let (int, frac) = match unsigned.find('.') {
+            Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+            None => (unsigned, ""),
+        };
+        if int.is_empty() && frac.is_empty() {
+            return Err(err());
+        }
+        if !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(err());
+        }
+        let digits = format!("{}{}", int, frac);
+        let mut unscaled = BigInt::parse_bytes(digits.as_bytes(), 10).ok_or_else(err)?;
+        if negative {
+            unscaled = -unscaled;
+        }
+        Ok(Decimal::new(unscaled, frac.len()))
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DECIMAL_NEWTYPE, &self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl<'de> Visitor<'de> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a decimal number as a string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Decimal::from_str(v).map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Decimal::new(v, 0))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Decimal::new(v, 0))
+            }
+        }
+
+        deserializer.deserialize_str(DecimalVisitor)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Decimal> for Decimal {
+    fn from(d: rust_decimal::Decimal) -> Self {
+        Decimal::new(d.mantissa(), d.scale() as usize)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl std::convert::TryFrom<Decimal> for rust_decimal::Decimal {
+    type Error = AvrowErr;
+
+    fn try_from(d: Decimal) -> Result<Self, Self::Error> {
+        use num_traits::ToPrimitive;
+        let err = || AvrowErr::DecimalParseErr(d.to_string());
+        let mantissa = d.unscaled.to_i128().ok_or_else(err)?;
+        rust_decimal::Decimal::try_from_i128_with_scale(mantissa, d.scale as u32).map_err(|_| err())
+    }
+}
+
+// Parses the hyphenated string form of a uuid to its 16 bytes.
+pub(crate) fn parse_uuid(s: &str) -> Result<[u8; 16], AvrowErr> {
+    let err = || AvrowErr::InvalidUuid(s.to_string());
+    if s.len() != 36 {
+        return Err(err());
+    }
+    let mut nibbles = Vec::with_capacity(32);
+    for (idx, c) in s.chars().enumerate() {
+        match idx {
+            8 | 13 | 18 | 23 if c == '-' => {}
+            8 | 13 | 18 | 23 => return Err(err()),
+            _ => nibbles.push(c.to_digit(16).ok_or_else(err)? as u8),
+        }
+    }
+    let mut bytes = [0u8; 16];
+    for (b, pair) in bytes.iter_mut().zip(nibbles.chunks(2)) {
+        *b = pair[0] << 4 | pair[1];
+    }
+    Ok(bytes)
+}
+
+// Formats 16 bytes as the hyphenated string form of a uuid.
+pub(crate) fn format_uuid(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(36);
+    for (idx, b) in bytes.iter().enumerate() {
+        if idx == 4 || idx == 6 || idx == 8 || idx == 10 {
+            s.push('-');
+        }
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+// Converts the decoded string or fixed value of a uuid schema to a uuid value.
+pub(crate) fn uuid_from_raw(raw: Value) -> Result<Value, AvrowErr> {
+    match raw {
+        Value::Str(s) => {
+            let _ = parse_uuid(&s)?;
+            Ok(Value::Uuid(s))
+        }
+        Value::Fixed(ref b) if b.len() == 16 => Ok(Value::Uuid(format_uuid(b))),
+        other => Err(AvrowErr::InvalidUuid(format!("{:?}", other))),
+    }
+}
+
+// Encodes a duration as three little-endian unsigned integers.
+pub(crate) fn duration_to_bytes(months: u32, days: u32, millis: u32) -> [u8; 12] {
+    let mut bytes = [0u8; 12];
+    bytes[..4].copy_from_slice(&months.to_le_bytes());
+    bytes[4..8].copy_from_slice(&days.to_le_bytes());
+    bytes[8..].copy_from_slice(&millis.to_le_bytes());
+    bytes
+}
+
+// Converts the decoded fixed value of a duration schema to a duration value.
+pub(crate) fn duration_from_raw(raw: Value) -> Result<Value, AvrowErr> {
+    match raw {
+        Value::Fixed(b) => {
+            if b.len() != 12 {
+                return Err(AvrowErr::FixedValueLenMismatch {
+                    found: b.len(),
+                    expected: 12,
+                });
+            }
+            let le_u32 = |i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+            Ok(Value::Duration {
+                months: le_u32(0),
+                days: le_u32(4),
+                millis: le_u32(8),
+            })
+        }
+        other => Err(AvrowErr::SchemaDataValidationFailed(
+            format!("{:?}", other),
+            "duration".to_string(),
+        )),
+    }
+}
+
+// Converts a time or timestamp value to the precision of a schema of the same kind, e.g. a
+// `TimestampMicros` written to a `timestamp-millis` schema. Converting to a coarser precision
+// truncates towards the past. Returns `None` if the value is not a temporal of that kind.
+pub(crate) fn convert_temporal(value: &Value, schema: &Variant) -> Option<Result<Value, AvrowErr>> {
+    let to_micros = |millis: i64| {
+        millis
+            .checked_mul(1000)
+            .ok_or_else(|| AvrowErr::TemporalOutOfRange(format!("{} millis", millis)))
+    };
+    let converted = match (value, schema) {
+        (Value::TimeMillis(v), Variant::TimeMicros) => Ok(Value::TimeMicros(i64::from(*v) * 1000)),
+        (Value::TimeMicros(v), Variant::TimeMillis) => i32::try_from(v.div_euclid(1000))
+            .map(Value::TimeMillis)
+            .map_err(|_| AvrowErr::TemporalOutOfRange(format!("{} micros since midnight", v))),
+        (Value::TimestampMillis(v), Variant::TimestampMicros) => {
+            to_micros(*v).map(Value::TimestampMicros)
+        }
+        (Value::TimestampMicros(v), Variant::TimestampMillis) => {
+            Ok(Value::TimestampMillis(v.div_euclid(1000)))
+        }
+        (Value::LocalTimestampMillis(v), Variant::LocalTimestampMicros) => {
+            to_micros(*v).map(Value::LocalTimestampMicros)
+        }
+        (Value::LocalTimestampMicros(v), Variant::LocalTimestampMillis) => {
+            Ok(Value::LocalTimestampMillis(v.div_euclid(1000)))
+        }
+        _ => return None,
+    };
+    Some(converted)
+}
+
+#[cfg(feature = "uuid")]
+impl std::convert::TryFrom<Value> for uuid::Uuid {
+    type Error = AvrowErr;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Uuid(ref s) => Ok(uuid::Uuid::from_bytes(parse_uuid(s)?)),
+            _ => Err(AvrowErr::ExpectedVariantNotFound),
+        }
+    }
+}
+
+// Wraps the decoded int or long of a temporal schema in its logical value.
+pub(crate) fn temporal_from_raw(schema: &Variant, raw: Value) -> Result<Value, AvrowErr> {
+    let value = match (schema, raw) {
+        (Variant::Date, Value::Int(v)) => Value::Date(v),
+        (Variant::TimeMillis, Value::Int(v)) => Value::TimeMillis(v),
+        (Variant::TimeMicros, Value::Long(v)) => Value::TimeMicros(v),
+        (Variant::TimestampMillis, Value::Long(v)) => Value::TimestampMillis(v),
+        (Variant::TimestampMicros, Value::Long(v)) => Value::TimestampMicros(v),
+        (Variant::LocalTimestampMillis, Value::Long(v)) => Value::LocalTimestampMillis(v),
+        (Variant::LocalTimestampMicros, Value::Long(v)) => Value::LocalTimestampMicros(v),
+        (schema, raw) => {
+            return Err(AvrowErr::SchemaDataValidationFailed(
+                format!("{:?}", raw),
+                format!("{:?}", schema),
+            ))
+        }
+    };
+    Ok(value)
+}
+
+// The chrono conversions below do the epoch math for the temporal logical types.
+
+#[cfg(feature = "chrono")]
+fn unix_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1970, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("unix epoch is a valid date")
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) fn days_since_epoch(date: NaiveDate) -> i32 {
+    date.signed_duration_since(unix_epoch().date()).num_days() as i32
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) fn micros_since_midnight(time: NaiveTime) -> i64 {
+    i64::from(time.num_seconds_from_midnight()) * 1_000_000 + i64::from(time.nanosecond() / 1000)
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) fn micros_since_epoch(datetime: NaiveDateTime) -> i64 {
+    let datetime = Utc.from_utc_datetime(&datetime);
+    datetime.timestamp() * 1_000_000 + i64::from(datetime.timestamp_subsec_micros())
+}
+
+#[cfg(feature = "chrono")]
+fn date_from_days(days: i32) -> Result<NaiveDate, AvrowErr> {
+    unix_epoch()
+        .date()
+        .checked_add_signed(chrono::Duration::days(i64::from(days)))
+        .ok_or_else(|| AvrowErr::TemporalOutOfRange(format!("{} days", days)))
+}
+
+#[cfg(feature = "chrono")]
+fn time_from_micros(micros: i64) -> Result<NaiveTime, AvrowErr> {
+    let err = || AvrowErr::TemporalOutOfRange(format!("{} micros after midnight", micros));
+    if micros < 0 {
+        return Err(err());
+    }
+    let secs = u32::try_from(micros / 1_000_000).map_err(|_| err())?;
+    let nanos = (micros % 1_000_000) as u32 * 1000;
+    NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos).ok_or_else(err)
+}
+
+#[cfg(feature = "chrono")]
+fn datetime_from_micros(micros: i64) -> Result<NaiveDateTime, AvrowErr> {
+    let secs = micros.div_euclid(1_000_000);
+    let nanos = micros.rem_euclid(1_000_000) as u32 * 1000;
+    Utc.timestamp_opt(secs, nanos)
+        .single()
+        .map(|d| d.naive_utc())
+        .ok_or_else(|| AvrowErr::TemporalOutOfRange(format!("{} micros since epoch", micros)))
+}
+
+#[cfg(feature = "chrono")]
+fn millis_to_micros(millis: i64) -> Result<i64, AvrowErr> {
+    millis
+        .checked_mul(1000)
+        .ok_or_else(|| AvrowErr::TemporalOutOfRange(format!("{} millis", millis)))
+}
+
+// Parses the string form of a chrono type, as produced by its `Serialize` impl, to a temporal value.
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_temporal(s: &str, schema: &Variant) -> Result<Value, AvrowErr> {
+    let err = |ty: &str| AvrowErr::TemporalParseErr(ty.to_string(), s.to_string());
+    let time = || NaiveTime::from_str(s).map(micros_since_midnight);
+    let timestamp = || DateTime::parse_from_rfc3339(s).map(|d| micros_since_epoch(d.naive_utc()));
+    let local_timestamp = || NaiveDateTime::from_str(s).map(micros_since_epoch);
+    let value = match schema {
+        Variant::Date => Value::from(NaiveDate::from_str(s).map_err(|_| err("date"))?),
+        Variant::TimeMillis => Value::TimeMillis((time().map_err(|_| err("time"))? / 1000) as i32),
+        Variant::TimeMicros => Value::TimeMicros(time().map_err(|_| err("time"))?),
+        Variant::TimestampMillis => {
+            Value::TimestampMillis(timestamp().map_err(|_| err("timestamp"))?.div_euclid(1000))
+        }
+        Variant::TimestampMicros => {
+            Value::TimestampMicros(timestamp().map_err(|_| err("timestamp"))?)
+        }
+        Variant::LocalTimestampMillis => Value::LocalTimestampMillis(
+            local_timestamp()
+                .map_err(|_| err("local timestamp"))?
+                .div_euclid(1000),
+        ),
+        Variant::LocalTimestampMicros => {
+            Value::LocalTimestampMicros(local_timestamp().map_err(|_| err("local timestamp"))?)
+        }
+        other => return Err(err(&format!("{:?}", other))),
+    };
+    Ok(value)
+}
+
+// Formats a temporal value as the string expected by the `Deserialize` impl of its chrono type.
+#[cfg(feature = "chrono")]
+pub(crate) fn format_temporal(value: &Value) -> Option<Result<String, AvrowErr>> {
+    let timestamp = |micros| {
+        datetime_from_micros(micros).map(|d| {
+            Utc.from_utc_datetime(&d)
+                .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+        })
+    };
+    let local_timestamp =
+        |micros| datetime_from_micros(micros).map(|d| d.format("%Y-%m-%dT%H:%M:%S%.f").to_string());
+    let formatted = match *value {
+        Value::Date(v) => date_from_days(v).map(|d| d.to_string()),
+        Value::TimeMillis(v) => time_from_micros(i64::from(v) * 1000).map(|t| t.to_string()),
+        Value::TimeMicros(v) => time_from_micros(v).map(|t| t.to_string()),
+        Value::TimestampMillis(v) => millis_to_micros(v).and_then(timestamp),
+        Value::TimestampMicros(v) => timestamp(v),
+        Value::LocalTimestampMillis(v) => millis_to_micros(v).and_then(local_timestamp),
+        Value::LocalTimestampMicros(v) => local_timestamp(v),
+        _ => return None,
+    };
+    Some(formatted)
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for NaiveDate {
+    type Error = AvrowErr;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Date(v) => date_from_days(v),
+            _ => Err(AvrowErr::ExpectedVariantNotFound),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for NaiveTime {
+    type Error = AvrowErr;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::TimeMillis(v) => time_from_micros(i64::from(v) * 1000),
+            Value::TimeMicros(v) => time_from_micros(v),
+            _ => Err(AvrowErr::ExpectedVariantNotFound),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for NaiveDateTime {
+    type Error = AvrowErr;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::LocalTimestampMillis(v) => datetime_from_micros(millis_to_micros(v)?),
+            Value::LocalTimestampMicros(v) => datetime_from_micros(v),
+            _ => Err(AvrowErr::ExpectedVariantNotFound),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for DateTime<Utc> {
+    type Error = AvrowErr;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let datetime = match value {
+            Value::TimestampMillis(v) => datetime_from_micros(millis_to_micros(v)?)?,
+            Value::TimestampMicros(v) => datetime_from_micros(v)?,
+            _ => return Err(AvrowErr::ExpectedVariantNotFound),
+        };
+        Ok(Utc.from_utc_datetime(&datetime))
+    }
+}
+
+/// A custom logical type, annotating an underlying avro schema with a `logicalType` attribute
+/// that is not defined by the spec. Custom logical types are registered on a schema with
+/// [`Schema::register_logical_type`](struct.Schema.html#method.register_logical_type).
+/// Values are converted to their underlying avro value before they are written and converted
+/// back from the underlying avro value when they are read.
+///
+/// ```rust
+/// use avrow::{AvrowErr, LogicalType, Value};
+///
+/// // Represents a percentage as a string such as "42%", stored as an avro int.
+/// #[derive(Debug)]
+/// struct Percent;
+///
+/// impl LogicalType for Percent {
+///     fn name(&self) -> &str {
+///         "percent"
+///     }
+///
+///     fn to_underlying(&self, value: &Value) -> Result<Value, AvrowErr> {
+///         match value {
+///             Value::Str(s) => s
+///                 .trim_end_matches('%')
+///                 .parse::<i32>()
+///                 .map(Value::Int)
+///                 .map_err(|e| AvrowErr::LogicalTypeErr(e.to_string())),
+///             other => Ok(other.clone()),
+///         }
+///     }
+///
+///     fn to_logical(&self, value: Value) -> Result<Value, AvrowErr> {
+///         match value {
+///             Value::Int(i) => Ok(Value::Str(format!("{}%", i))),
+///             other => Ok(other),
+///         }
+///     }
+/// }
+/// ```
+pub trait LogicalType: Debug + Send + Sync {
+    /// The name of the logical type, as it appears in the `logicalType` attribute of a schema.
+    fn name(&self) -> &str;
+
+    /// Validates the attributes of a schema annotated with this logical type.
+    /// This is called for every annotated schema when the logical type is registered.
+    fn validate_schema(&self, _schema: &Map<String, JsonValue>) -> Result<(), AvrowErr> {
+        Ok(())
+    }
+
+    /// Converts a value to the underlying avro value that gets encoded.
+    fn to_underlying(&self, value: &Value) -> Result<Value, AvrowErr>;
+
+    /// Converts a decoded underlying avro value to the value handed to the user.
+    fn to_logical(&self, value: Value) -> Result<Value, AvrowErr>;
+}
+
+// A registered custom logical type. Logical types are compared by their names.
+#[derive(Debug, Clone)]
+pub(crate) struct CustomLogical(pub(crate) Arc<dyn LogicalType>);
+
+impl PartialEq for CustomLogical {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{max_fixed_precision, sign_extend, Decimal};
+    use std::str::FromStr;
+
+    #[test]
+    fn decimal_display_and_parse() {
+        for s in &["0", "12.34", "-12.34", "0.005", "-0.5", "1000"] {
+            let d = Decimal::from_str(s).unwrap();
+            assert_eq!(&d.to_string(), s);
+        }
+        assert!(Decimal::from_str("12.3.4").is_err());
+        assert!(Decimal::from_str("abc").is_err());
+        assert!(Decimal::from_str("-").is_err());
+    }
+
+    #[test]
+    fn decimal_twos_complement_roundtrip() {
+        let d = Decimal::new(-129, 2);
+        assert_eq!(d.to_be_bytes(), vec![0xff, 0x7f]);
+        assert_eq!(Decimal::from_be_bytes(&d.to_be_bytes(), 2), d);
+        assert_eq!(
+            sign_extend(&d.to_be_bytes(), 4).unwrap(),
+            vec![0xff, 0xff, 0xff, 0x7f]
+        );
+        assert!(sign_extend(&d.to_be_bytes(), 1).is_err());
+    }
+
+    #[test]
+    fn decimal_rescale() {
+        let d = Decimal::new(12, 1);
+        assert_eq!(d.rescale(3).unwrap(), Decimal::new(1200, 3));
+        assert_eq!(Decimal::new(1200, 3).rescale(1).unwrap(), d);
+        assert!(Decimal::new(1201, 3).rescale(1).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn temporal_epoch_math() {
+        use super::{format_temporal, parse_temporal};
+        use crate::schema::Variant;
+        use crate::value::Value;
+        use chrono::{NaiveDate, NaiveDateTime};
+        use std::convert::TryFrom;
+
+        let date = NaiveDate::from_ymd_opt(1969, 12, 31).unwrap();
+        assert_eq!(Value::from(date), Value::Date(-1));
+        assert_eq!(NaiveDate::try_from(Value::Date(-1)).unwrap(), date);
+
+        let ts = parse_temporal("2020-02-03T04:05:06.007Z", &Variant::TimestampMillis).unwrap();
+        assert_eq!(ts, Value::TimestampMillis(1_580_702_706_007));
+        assert_eq!(
+            format_temporal(&ts).unwrap().unwrap(),
+            "2020-02-03T04:05:06.007Z"
+        );
+
+        let local = NaiveDateTime::try_from(Value::LocalTimestampMicros(-1)).unwrap();
+        assert_eq!(local.to_string(), "1969-12-31 23:59:59.999999");
+        assert_eq!(Value::from(local), Value::LocalTimestampMicros(-1));
+
+        assert!(parse_temporal("noon", &Variant::TimeMillis).is_err());
+        assert!(format_temporal(&Value::TimeMillis(-1)).unwrap().is_err());
+    }
+
+    #[test]
+    fn uuid_parse_and_format() {
+        use super::{format_uuid, parse_uuid};
+
+        let s = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+        let bytes = parse_uuid(s).unwrap();
+        assert_eq!(bytes[0], 0xf8);
+        assert_eq!(format_uuid(&bytes), s);
+        assert!(parse_uuid("F81D4FAE-7DEC-11D0-A765-00A0C91E6BF6").is_ok());
+        assert!(parse_uuid("f81d4fae7dec11d0a76500a0c91e6bf6").is_err());
+        assert!(parse_uuid("f81d4fae-7dec-11d0-a765-00a0c91e6bfg").is_err());
+        assert!(parse_uuid("f81d4fae-7dec-11d0+a765-00a0c91e6bf6").is_err());
+    }
+
+    #[test]
+    fn fixed_precision_limits() {
+        assert_eq!(max_fixed_precision(1), 2);
+        assert_eq!(max_fixed_precision(4), 9);
+        assert_eq!(max_fixed_precision(16), 38);
+    }
+}
@@ -0,0 +1,170 @@
+//! Contains routines for encoding and decoding standalone avro messages.
+//! Unlike a datafile, a message does not embed its schema and instead refers to it by a
+//! fingerprint or a schema id, which is looked up in a [SchemaStore](trait.SchemaStore.html) when decoding.
+
+use crate::error::AvrowErr;
+use crate::resolution::ResolutionPlan;
+use crate::schema::Schema;
+use crate::store::SchemaStore;
+use crate::value::Value;
+use std::io::{Cursor, Read};
+
+// Two byte marker that starts a single object encoded message.
+const SINGLE_OBJECT_MARKER: [u8; 2] = [0xC3, 0x01];
+// Magic byte that starts a message in the Confluent wire format.
+const WIRE_FORMAT_MAGIC: u8 = 0;
+
+/// Encodes a value with the [single object encoding](https://avro.apache.org/docs/current/spec.html#single_object_encoding).
+/// The encoded message starts with the `0xC3 0x01` marker, followed by the little-endian
+/// rabin64 fingerprint of the schema and the avro binary encoding of the value.
+///
+/// ```rust
+/// use avrow::{to_single_object, Schema};
+/// use std::str::FromStr;
+///
+/// let schema = Schema::from_str(r##""string""##).unwrap();
+/// let msg = to_single_object("hello", &schema).unwrap();
+/// assert_eq!(&msg[..2], &[0xC3, 0x01]);
+/// ```
+pub fn to_single_object<T: Into<Value>>(value: T, schema: &Schema) -> Result<Vec<u8>, AvrowErr> {
+    let value = value.into();
+    schema.validate(&value)?;
+
+    let mut buf = SINGLE_OBJECT_MARKER.to_vec();
+    buf.extend_from_slice(&schema.canonical_form().rabin64().to_le_bytes());
+    value.encode(&mut buf, schema.variant(), &schema.cxt)?;
+    Ok(buf)
+}
+
+/// Decodes a [single object encoded](https://avro.apache.org/docs/current/spec.html#single_object_encoding)
+/// message. The writer schema is looked up in the given store by the fingerprint in the message,
+/// and the value is resolved against the reader schema.
+pub fn from_single_object<S: SchemaStore + ?Sized>(
+    msg: &[u8],
+    store: &S,
+    reader_schema: &Schema,
+) -> Result<Value, AvrowErr> {
+    let mut reader = Cursor::new(msg);
+    let fingerprint = read_single_object_header(&mut reader)?;
+    let writer_schema = store.get_by_fingerprint(fingerprint)?;
+
+    ResolutionPlan::new(reader_schema, &writer_schema)?.decode(&mut reader)
+}
+
+// Reads the marker and the schema fingerprint that start a single object encoded message.
+pub(crate) fn read_single_object_header<R: Read>(reader: &mut R) -> Result<i64, AvrowErr> {
+    let mut marker = [0u8; 2];
+    reader
+        .read_exact(&mut marker)
+        .map_err(AvrowErr::DecodeFailed)?;
+    if marker != SINGLE_OBJECT_MARKER {
+        return Err(AvrowErr::InvalidSingleObjectMarker);
+    }
+
+    let mut fingerprint = [0u8; 8];
+    reader
+        .read_exact(&mut fingerprint)
+        .map_err(AvrowErr::DecodeFailed)?;
+    Ok(i64::from_le_bytes(fingerprint))
+}
+
+/// Encodes a value in the [Confluent wire format](https://docs.confluent.io/platform/current/schema-registry/serdes-develop/index.html#wire-format)
+/// used for Kafka messages. The encoded message starts with a zero magic byte, followed by the
+/// big-endian schema id as assigned by the schema registry and the avro binary encoding of the value.
+///
+/// ```rust
+/// use avrow::{to_wire_format, Schema};
+/// use std::str::FromStr;
+///
+/// let schema = Schema::from_str(r##""string""##).unwrap();
+/// let msg = to_wire_format("hello", 7, &schema).unwrap();
+/// assert_eq!(&msg[..5], &[0, 0, 0, 0, 7]);
+/// ```
+pub fn to_wire_format<T: Into<Value>>(
+    value: T,
+    schema_id: u32,
+    schema: &Schema,
+) -> Result<Vec<u8>, AvrowErr> {
+    let value = value.into();
+    schema.validate(&value)?;
+
+    let mut buf = vec![WIRE_FORMAT_MAGIC];
+    buf.extend_from_slice(&schema_id.to_be_bytes());
+    value.encode(&mut buf, schema.variant(), &schema.cxt)?;
+    Ok(buf)
+}
+
+/// Decodes a message in the [Confluent wire format](https://docs.confluent.io/platform/current/schema-registry/serdes-develop/index.html#wire-format).
+/// The writer schema is looked up in the given store by the schema id in the message,
+/// and the value is resolved against the reader schema.
+pub fn from_wire_format<S: SchemaStore + ?Sized>(
+    msg: &[u8],
+    store: &S,
+    reader_schema: &Schema,
+) -> Result<Value, AvrowErr> {
+    let mut reader = Cursor::new(msg);
+    let mut header = [0u8; 5];
+    reader
+        .read_exact(&mut header)
+        .map_err(AvrowErr::DecodeFailed)?;
+    if header[0] != WIRE_FORMAT_MAGIC {
+        return Err(AvrowErr::InvalidWireFormatMagic(header[0]));
+    }
+
+    let schema_id = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    let writer_schema = store.get_by_id(schema_id)?;
+
+    ResolutionPlan::new(reader_schema, &writer_schema)?.decode(&mut reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_single_object, from_wire_format, to_single_object, to_wire_format};
+    use crate::schema::Schema;
+    use crate::store::{InMemorySchemaStore, SchemaStore};
+    use crate::value::Value;
+    use std::str::FromStr;
+
+    #[test]
+    fn single_object_roundtrip_with_resolution() {
+        let writer_schema = Schema::from_str(r##""int""##).unwrap();
+        let fingerprint = writer_schema.canonical_form().rabin64();
+        let msg = to_single_object(42, &writer_schema).unwrap();
+        assert_eq!(&msg[2..10], &fingerprint.to_le_bytes());
+
+        let store = InMemorySchemaStore::new();
+        store.register(writer_schema).unwrap();
+
+        let reader_schema = Schema::from_str(r##""long""##).unwrap();
+        let value = from_single_object(&msg, &store, &reader_schema).unwrap();
+        assert_eq!(value, Value::Long(42));
+
+        let mut bad_marker = msg.clone();
+        bad_marker[1] = 0x02;
+        assert!(from_single_object(&bad_marker, &store, &reader_schema).is_err());
+
+        let unknown = to_single_object("hello", &Schema::from_str(r##""string""##).unwrap());
+        assert!(from_single_object(&unknown.unwrap(), &store, &reader_schema).is_err());
+    }
+
+    #[test]
+    fn wire_format_roundtrip_with_resolution() {
+        let store = InMemorySchemaStore::new();
+        let writer_schema = Schema::from_str(r##""string""##).unwrap();
+        let msg = to_wire_format("avro", 1, &writer_schema).unwrap();
+        assert_eq!(&msg[..5], &[0, 0, 0, 0, 1]);
+        let id = store.register(writer_schema).unwrap();
+        assert_eq!(id, 1);
+
+        let reader_schema = Schema::from_str(r##""bytes""##).unwrap();
+        let value = from_wire_format(&msg, &store, &reader_schema).unwrap();
+        assert_eq!(value, Value::Bytes(b"avro".to_vec()));
+
+        let mut bad_magic = msg.clone();
+        bad_magic[0] = 1;
+        assert!(from_wire_format(&bad_magic, &store, &reader_schema).is_err());
+        let mut unknown_id = msg;
+        unknown_id[4] = 2;
+        assert!(from_wire_format(&unknown_id, &store, &reader_schema).is_err());
+    }
+}
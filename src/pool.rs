@@ -0,0 +1,184 @@
+//! Contains a pool of worker threads that process jobs in parallel and hand back their outputs,
+//! either in the order the jobs were submitted or as soon as they finish. Used for compressing
+//! and decoding datafile blocks in parallel.
+
+use crate::error::AvrowErr;
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+pub(crate) struct WorkerPool<J, O> {
+    // dropped first on drop, so that the workers stop
+    jobs: Option<Sender<(u64, J)>>,
+    // a job whose processing panicked yields an error in place of its output
+    results: Receiver<(u64, Result<O, AvrowErr>)>,
+    workers: Vec<JoinHandle<()>>,
+    // sequence number of the next job to submit
+    submitted: u64,
+    // sequence number of the next output to hand back in order
+    next: u64,
+    // number of jobs whose output has not been handed back yet
+    in_flight: usize,
+    // outputs that finished before the ones submitted earlier
+    finished: BTreeMap<u64, Result<O, AvrowErr>>,
+}
+
+impl<J: Send + 'static, O: Send + 'static> WorkerPool<J, O> {
+    // Spawns `threads` workers, each calling `process` on the jobs it receives.
+    pub(crate) fn new<F>(threads: usize, process: F) -> Self
+    where
+        F: Fn(J) -> O + Send + Sync + 'static,
+    {
+        let (jobs, job_rx) = channel::<(u64, J)>();
+        let (result_tx, results) = channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let process = Arc::new(process);
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let job_rx = job_rx.clone();
+                let result_tx = result_tx.clone();
+                let process = process.clone();
+                thread::spawn(move || loop {
+                    // the lock is released before processing the job
+                    let job = match job_rx.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok((seq, job)) => {
+                            // a panic is sent back as an error, as the pool would otherwise wait
+                            // forever for the output of this job
+                            let output = catch_unwind(AssertUnwindSafe(|| process(job)))
+                                .map_err(|_| AvrowErr::WorkerStopped);
+                            if result_tx.send((seq, output)).is_err() {
+                                return;
+                            }
+                        }
+                        // the pool was dropped
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+
+        WorkerPool {
+            jobs: Some(jobs),
+            results,
+            workers,
+            submitted: 0,
+            next: 0,
+            in_flight: 0,
+            finished: BTreeMap::new(),
+        }
+    }
+
+    // Number of jobs whose output has not been handed back yet.
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    // Fails if the workers are gone.
+    pub(crate) fn submit(&mut self, job: J) -> Result<(), AvrowErr> {
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send((self.submitted, job)).ok())
+            .ok_or(AvrowErr::WorkerStopped)?;
+        self.submitted += 1;
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    // Blocks until the output of the earliest submitted job is available. Returns `None` if no job
+    // is in flight, and an error if processing that job panicked.
+    pub(crate) fn next_in_order(&mut self) -> Result<Option<O>, AvrowErr> {
+        if self.in_flight == 0 {
+            return Ok(None);
+        }
+        let output = loop {
+            if let Some(output) = self.finished.remove(&self.next) {
+                break output;
+            }
+            let (seq, output) = self.results.recv().map_err(|_| AvrowErr::WorkerStopped)?;
+            self.finished.insert(seq, output);
+        };
+        self.next += 1;
+        self.in_flight -= 1;
+        output.map(Some)
+    }
+
+    // Blocks until the output of any job is available. Returns `None` if no job is in flight, and
+    // an error if processing that job panicked. This must not be mixed with `next_in_order` on the
+    // same pool.
+    pub(crate) fn next_finished(&mut self) -> Result<Option<O>, AvrowErr> {
+        if self.in_flight == 0 {
+            return Ok(None);
+        }
+        let (_, output) = self.results.recv().map_err(|_| AvrowErr::WorkerStopped)?;
+        self.in_flight -= 1;
+        output.map(Some)
+    }
+}
+
+impl<J, O> Drop for WorkerPool<J, O> {
+    fn drop(&mut self) {
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkerPool;
+    use crate::error::AvrowErr;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn outputs_in_submission_order() {
+        let mut pool = WorkerPool::new(4, |i: u64| {
+            // later jobs finish first
+            thread::sleep(Duration::from_millis(20 - i * 2));
+            i * 10
+        });
+        for i in 0..10 {
+            pool.submit(i).unwrap();
+        }
+        assert_eq!(pool.in_flight(), 10);
+        let outputs: Vec<_> = std::iter::from_fn(|| pool.next_in_order().unwrap()).collect();
+        assert_eq!(outputs, (0..10).map(|i| i * 10).collect::<Vec<_>>());
+        assert_eq!(pool.in_flight(), 0);
+    }
+
+    #[test]
+    fn outputs_as_finished() {
+        let mut pool = WorkerPool::new(2, |i: u64| {
+            thread::sleep(Duration::from_millis(20 - i * 10));
+            i
+        });
+        pool.submit(0).unwrap();
+        pool.submit(1).unwrap();
+        assert_eq!(pool.next_finished().unwrap(), Some(1));
+        assert_eq!(pool.next_finished().unwrap(), Some(0));
+        assert_eq!(pool.next_finished().unwrap(), None);
+    }
+
+    #[test]
+    fn panicking_job_yields_error() {
+        let mut pool = WorkerPool::new(2, |i: u64| {
+            if i == 1 {
+                panic!("job failed");
+            }
+            i
+        });
+        for i in 0..3 {
+            pool.submit(i).unwrap();
+        }
+        assert_eq!(pool.next_in_order().unwrap(), Some(0));
+        assert!(matches!(pool.next_in_order(), Err(AvrowErr::WorkerStopped)));
+        assert_eq!(pool.next_in_order().unwrap(), Some(2));
+    }
+}
@@ -0,0 +1,735 @@
+//! Contains routines for parsing an [Avro protocol](https://avro.apache.org/docs/current/spec.html#Protocol+Declaration).
+//! Protocols are written as JSON and can be provided as .avpr files. The named types of a protocol
+//! are parsed into one registry, which the messages of the protocol refer to.
+
+use crate::error::{AvrowErr, AvrowResult};
+use crate::schema::common::{Field, Name};
+use crate::schema::{Registry, Variant};
+use crate::value::Value;
+use indexmap::IndexMap;
+use serde_json::{Map, Value as JsonValue};
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+/// Represents an avro protocol, which declares named types and the messages
+/// that are exchanged using them.
+/// ```rust
+/// use avrow::Protocol;
+/// use std::str::FromStr;
+///
+/// let protocol = Protocol::from_str(r##"
+///     {
+///         "protocol": "HelloWorld",
+///         "namespace": "com.acme",
+///         "types": [
+///             {"name": "Greeting", "type": "record", "fields": [{"name": "message", "type": "string"}]}
+///         ],
+///         "messages": {
+///             "hello": {
+///                 "request": [{"name": "greeting", "type": "Greeting"}],
+///                 "response": "Greeting"
+///             }
+///         }
+///     }
+/// "##).unwrap();
+///
+/// assert_eq!(protocol.types().collect::<Vec<_>>(), vec!["com.acme.Greeting"]);
+/// assert!(protocol.message("hello").is_some());
+/// ```
+#[derive(Debug)]
+pub struct Protocol {
+    inner: JsonValue,
+    // the protocol in a defined attribute order, which the md5 hash is computed from
+    #[cfg(feature = "md5")]
+    normalized: JsonValue,
+    name: Name,
+    doc: Option<String>,
+    // fullnames of the named types, in the order they are declared
+    types: Vec<String>,
+    messages: IndexMap<String, Message>,
+    // Schema context shared by the types and the messages of the protocol
+    cxt: Registry,
+}
+
+/// Represents a message of an avro protocol.
+#[derive(Debug)]
+pub struct Message {
+    doc: Option<String>,
+    request: IndexMap<String, Field>,
+    response: Variant,
+    // union of `string` and the declared error types
+    errors: Variant,
+    one_way: bool,
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl std::str::FromStr for Protocol {
+    type Err = AvrowErr;
+    /// Parse an avro protocol from a JSON string
+    fn from_str(protocol: &str) -> Result<Self, Self::Err> {
+        let protocol_json =
+            serde_json::from_str(protocol).map_err(|e| AvrowErr::SchemaParseErr(e.into()))?;
+        Protocol::parse_imp(protocol_json)
+    }
+}
+
+impl Protocol {
+    /// Parses an avro protocol from a JSON protocol in a file, such as an .avpr file.
+    pub fn from_path<P: AsRef<Path> + Debug>(path: P) -> AvrowResult<Self> {
+        let protocol_file = OpenOptions::new()
+            .read(true)
+            .open(&path)
+            .map_err(AvrowErr::SchemaParseErr)?;
+        let value = serde_json::from_reader(protocol_file)
+            .map_err(|e| AvrowErr::SchemaParseErr(e.into()))?;
+        Protocol::parse_imp(value)
+    }
+
+    fn parse_imp(protocol_json: JsonValue) -> AvrowResult<Self> {
+        let json = protocol_json
+            .as_object()
+            .ok_or_else(|| protocol_err("expected a json object"))?;
+        let mut name = match json.get("protocol") {
+            Some(JsonValue::String(name)) => Name::new(name)?,
+            _ => return Err(protocol_err("missing required `protocol` name")),
+        };
+        if name.namespace().is_none() {
+            if let Some(JsonValue::String(namespace)) = json.get("namespace") {
+                name.set_namespace(namespace)?;
+            }
+        }
+        let namespace = name.namespace().map(ToString::to_string);
+        let namespace = namespace.as_deref();
+
+        let mut cxt = Registry::for_protocol();
+        let mut types = vec![];
+        let mut error_types = vec![];
+        match json.get("types") {
+            Some(JsonValue::Array(type_schemas)) => {
+                for schema in type_schemas {
+                    let (fullname, is_error) = type_name(schema, namespace)?;
+                    cxt.parse_schema(schema, namespace)?;
+                    if is_error {
+                        error_types.push(fullname.clone());
+                    }
+                    types.push(fullname);
+                }
+            }
+            Some(_) => return Err(protocol_err("`types` must be a json array")),
+            None => {}
+        }
+
+        let mut messages = IndexMap::new();
+        match json.get("messages") {
+            Some(JsonValue::Object(message_schemas)) => {
+                for (message_name, message) in message_schemas {
+                    let message = match message {
+                        JsonValue::Object(message) => {
+                            parse_message(message, &mut cxt, namespace, &error_types)?
+                        }
+                        _ => return Err(protocol_err("expected message to be a json object")),
+                    };
+                    messages.insert(message_name.to_string(), message);
+                }
+            }
+            Some(_) => return Err(protocol_err("`messages` must be a json object")),
+            None => {}
+        }
+
+        Ok(Protocol {
+            doc: doc(json),
+            #[cfg(feature = "md5")]
+            normalized: normalize::Normalizer::new(namespace).protocol(json, &name)?,
+            inner: protocol_json,
+            name,
+            types,
+            messages,
+            cxt,
+        })
+    }
+
+    /// Returns the name of the protocol, without its namespace.
+    pub fn name(&self) -> &str {
+        &self.name.name
+    }
+
+    /// Returns the namespace of the protocol, which is the default namespace of its types.
+    pub fn namespace(&self) -> Option<&str> {
+        self.name.namespace()
+    }
+
+    /// Returns the documentation of the protocol.
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    /// Returns the fullnames of the named types declared by the protocol, in declaration order.
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().map(|t| t.as_str())
+    }
+
+    /// Returns the messages of the protocol with their names, in declaration order.
+    pub fn messages(&self) -> impl Iterator<Item = (&str, &Message)> {
+        self.messages.iter().map(|(name, m)| (name.as_str(), m))
+    }
+
+    /// Returns the message with the given name.
+    pub fn message(&self, name: &str) -> Option<&Message> {
+        self.messages.get(name)
+    }
+
+    /// Checks that the values, in the order of the parameters, are a valid request of the message.
+    pub fn validate_request(&self, message: &str, params: &[Value]) -> AvrowResult<()> {
+        let msg = self.get_message(message)?;
+        if msg.request.len() != params.len() {
+            return Err(AvrowErr::RequestParamCount {
+                message: message.to_string(),
+                expected: msg.request.len(),
+                found: params.len(),
+            });
+        }
+        for (field, value) in msg.request.values().zip(params) {
+            field.ty.validate(value, &self.cxt)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the value is a valid response of the message.
+    pub fn validate_response(&self, message: &str, value: &Value) -> AvrowResult<()> {
+        let msg = self.get_message(message)?;
+        msg.response.validate(value, &self.cxt)
+    }
+
+    /// Checks that the value is a valid error of the message, which is either a string
+    /// or one of the message's declared errors.
+    pub fn validate_error(&self, message: &str, value: &Value) -> AvrowResult<()> {
+        let msg = self.get_message(message)?;
+        msg.errors.validate(value, &self.cxt)
+    }
+
+    /// Returns the MD5 hash of the protocol, which identifies the protocol in the
+    /// [handshake](https://avro.apache.org/docs/current/spec.html#handshake) of avro rpc.
+    /// The hash is computed over the protocol's JSON as written by the Java implementation, with
+    /// the attributes in a fixed order and without attributes unknown to the spec, so it doesn't
+    /// depend on how the protocol was formatted.
+    #[cfg(feature = "md5")]
+    pub fn md5(&self) -> Vec<u8> {
+        let v = mdfive::compute(self.normalized.to_string().as_bytes());
+        v.to_vec()
+    }
+
+    fn get_message(&self, name: &str) -> AvrowResult<&Message> {
+        self.messages
+            .get(name)
+            .ok_or_else(|| AvrowErr::MessageNotFound(name.to_string()))
+    }
+}
+
+impl Message {
+    /// Returns the documentation of the message.
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    /// Returns the names of the request parameters, in order.
+    pub fn params(&self) -> impl Iterator<Item = &str> {
+        self.request.keys().map(|p| p.as_str())
+    }
+
+    /// Returns the fullnames of the error types declared by the message.
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        let variants = match &self.errors {
+            Variant::Union { variants } => variants.as_slice(),
+            _ => &[],
+        };
+        variants.iter().filter_map(|v| match v {
+            Variant::Named(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns true if the message is one-way, which has no response and no errors.
+    pub fn is_one_way(&self) -> bool {
+        self.one_way
+    }
+}
+
+fn parse_message(
+    message: &Map<String, JsonValue>,
+    cxt: &mut Registry,
+    namespace: Option<&str>,
+    error_types: &[String],
+) -> AvrowResult<Message> {
+    let request = match message.get("request") {
+        Some(JsonValue::Array(params)) => cxt.parse_record_fields(params, namespace)?,
+        _ => return Err(protocol_err("expected `request` to be a json array")),
+    };
+    let response = cxt.parse_schema(
+        message
+            .get("response")
+            .ok_or_else(|| protocol_err("missing required `response`"))?,
+        namespace,
+    )?;
+
+    let mut errors = vec![Variant::Str];
+    match message.get("errors") {
+        Some(JsonValue::Array(names)) => {
+            for error in names {
+                match cxt.parse_schema(error, namespace)? {
+                    Variant::Named(ref name) if error_types.contains(name) => {
+                        errors.push(Variant::Named(name.to_string()))
+                    }
+                    _ => return Err(protocol_err(&format!("`{}` is not an error type", error))),
+                }
+            }
+        }
+        Some(_) => return Err(protocol_err("expected `errors` to be a json array")),
+        None => {}
+    }
+
+    let one_way = match message.get("one-way") {
+        Some(JsonValue::Bool(one_way)) => *one_way,
+        Some(_) => return Err(protocol_err("expected `one-way` to be a boolean")),
+        None => false,
+    };
+    if one_way && (response != Variant::Null || errors.len() > 1) {
+        return Err(protocol_err(
+            "one-way messages must have a null response and no errors",
+        ));
+    }
+
+    Ok(Message {
+        doc: doc(message),
+        request,
+        response,
+        errors: Variant::Union { variants: errors },
+        one_way,
+    })
+}
+
+// Returns the fullname of a named type declared in a protocol, and whether it is an error.
+fn type_name(schema: &JsonValue, namespace: Option<&str>) -> AvrowResult<(String, bool)> {
+    if let JsonValue::Object(schema) = schema {
+        match schema.get("type") {
+            Some(JsonValue::String(ty))
+                if ["record", "error", "enum", "fixed"].contains(&&**ty) =>
+            {
+                let name = Name::from_json(schema, namespace)?;
+                return Ok((name.fullname(), ty == "error"));
+            }
+            _ => {}
+        }
+    }
+    Err(protocol_err(&format!(
+        "protocol types must be named types, found: {}",
+        schema
+    )))
+}
+
+fn doc(json: &Map<String, JsonValue>) -> Option<String> {
+    json.get("doc")
+        .and_then(|d| d.as_str())
+        .map(ToString::to_string)
+}
+
+fn protocol_err(msg: &str) -> AvrowErr {
+    AvrowErr::ProtocolParseErr(msg.to_string())
+}
+
+#[cfg(feature = "md5")]
+mod normalize {
+    use super::protocol_err;
+    use crate::error::{AvrowErr, AvrowResult};
+    use crate::schema::common::Name;
+    use serde_json::{Map, Value as JsonValue};
+    use std::collections::HashSet;
+
+    const PRIMITIVES: [&str; 8] = [
+        "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+    ];
+
+    // Attributes of logical types that are kept in the normalized json.
+    const LOGICAL_ATTRIBUTES: [&str; 3] = ["logicalType", "precision", "scale"];
+
+    // Writes a protocol's json with the attributes in the order the Java implementation writes them.
+    // Named types are written in full where they are first declared and by name afterwards, relative
+    // to the enclosing namespace.
+    pub(super) struct Normalizer {
+        namespace: Option<String>,
+        known: HashSet<String>,
+    }
+
+    impl Normalizer {
+        pub(super) fn new(namespace: Option<&str>) -> Self {
+            Normalizer {
+                namespace: namespace.map(ToString::to_string),
+                known: HashSet::new(),
+            }
+        }
+
+        pub(super) fn protocol(
+            mut self,
+            json: &Map<String, JsonValue>,
+            name: &Name,
+        ) -> AvrowResult<JsonValue> {
+            let mut normalized = Map::new();
+            normalized.insert("protocol".to_string(), name.name.clone().into());
+            if let Some(namespace) = name.namespace() {
+                normalized.insert("namespace".to_string(), namespace.into());
+            }
+            copy(json, &mut normalized, "doc");
+            let mut types = vec![];
+            if let Some(JsonValue::Array(schemas)) = json.get("types") {
+                for schema in schemas {
+                    types.push(self.schema(schema)?);
+                }
+            }
+            normalized.insert("types".to_string(), types.into());
+            let mut messages = Map::new();
+            if let Some(JsonValue::Object(message_schemas)) = json.get("messages") {
+                for (message_name, message) in message_schemas {
+                    if let JsonValue::Object(message) = message {
+                        messages.insert(message_name.to_string(), self.message(message)?);
+                    }
+                }
+            }
+            normalized.insert("messages".to_string(), messages.into());
+            Ok(normalized.into())
+        }
+
+        fn message(&mut self, json: &Map<String, JsonValue>) -> AvrowResult<JsonValue> {
+            let mut normalized = Map::new();
+            copy(json, &mut normalized, "doc");
+            if let Some(JsonValue::Array(params)) = json.get("request") {
+                normalized.insert("request".to_string(), self.fields(params)?);
+            }
+            if let Some(JsonValue::Bool(true)) = json.get("one-way") {
+                normalized.insert("response".to_string(), "null".into());
+                normalized.insert("one-way".to_string(), true.into());
+                return Ok(normalized.into());
+            }
+            if let Some(response) = json.get("response") {
+                normalized.insert("response".to_string(), self.schema(response)?);
+            }
+            match json.get("errors") {
+                Some(JsonValue::Array(errors)) if !errors.is_empty() => {
+                    normalized.insert("errors".to_string(), self.schema(&errors[..].into())?);
+                }
+                _ => {}
+            }
+            Ok(normalized.into())
+        }
+
+        fn fields(&mut self, fields: &[JsonValue]) -> AvrowResult<JsonValue> {
+            let mut normalized = vec![];
+            for field in fields {
+                let field = field
+                    .as_object()
+                    .ok_or_else(|| protocol_err("expected field to be a json object"))?;
+                let mut f = Map::new();
+                copy(field, &mut f, "name");
+                if let Some(ty) = field.get("type") {
+                    f.insert("type".to_string(), self.schema(ty)?);
+                }
+                copy(field, &mut f, "doc");
+                copy(field, &mut f, "default");
+                match field.get("order") {
+                    Some(JsonValue::String(order)) if order != "ascending" => {
+                        f.insert("order".to_string(), order.as_str().into());
+                    }
+                    _ => {}
+                }
+                copy(field, &mut f, "aliases");
+                normalized.push(JsonValue::Object(f));
+            }
+            Ok(normalized.into())
+        }
+
+        fn schema(&mut self, schema: &JsonValue) -> AvrowResult<JsonValue> {
+            let json = match schema {
+                JsonValue::String(name) if PRIMITIVES.contains(&name.as_str()) => {
+                    return Ok(schema.clone())
+                }
+                JsonValue::String(name) => {
+                    let mut name = Name::new(name)?;
+                    if let Some(namespace) = &self.namespace {
+                        name.set_namespace(namespace)?;
+                    }
+                    return Ok(self.reference(&name));
+                }
+                JsonValue::Array(variants) => {
+                    let mut normalized = vec![];
+                    for variant in variants {
+                        normalized.push(self.schema(variant)?);
+                    }
+                    return Ok(normalized.into());
+                }
+                JsonValue::Object(json) => json,
+                _ => return Err(AvrowErr::UnknownSchema),
+            };
+
+            let ty = match json.get("type") {
+                Some(JsonValue::String(ty)) => ty.as_str(),
+                Some(other) => return self.schema(other),
+                None => return Err(AvrowErr::UnknownSchema),
+            };
+            let mut normalized = Map::new();
+            match ty {
+                "record" | "error" | "enum" | "fixed" => {
+                    let name = Name::from_json(json, self.namespace.as_deref())?;
+                    if !self.known.insert(name.fullname()) {
+                        return Ok(self.reference(&name));
+                    }
+                    normalized.insert("type".to_string(), ty.into());
+                    normalized.insert("name".to_string(), name.name.clone().into());
+                    if name.namespace() != self.namespace.as_deref() {
+                        let namespace = name.namespace().unwrap_or("");
+                        normalized.insert("namespace".to_string(), namespace.into());
+                    }
+                    copy(json, &mut normalized, "doc");
+                    match ty {
+                        "enum" => {
+                            copy(json, &mut normalized, "symbols");
+                            copy(json, &mut normalized, "default");
+                        }
+                        "fixed" => copy(json, &mut normalized, "size"),
+                        _ => {
+                            let enclosing = std::mem::replace(
+                                &mut self.namespace,
+                                name.namespace().map(ToString::to_string),
+                            );
+                            if let Some(JsonValue::Array(fields)) = json.get("fields") {
+                                normalized.insert("fields".to_string(), self.fields(fields)?);
+                            }
+                            self.namespace = enclosing;
+                        }
+                    }
+                    copy_logical(json, &mut normalized);
+                    copy(json, &mut normalized, "aliases");
+                }
+                "array" | "map" => {
+                    let items = if ty == "array" { "items" } else { "values" };
+                    normalized.insert("type".to_string(), ty.into());
+                    if let Some(items_schema) = json.get(items) {
+                        normalized.insert(items.to_string(), self.schema(items_schema)?);
+                    }
+                    copy_logical(json, &mut normalized);
+                }
+                _ => {
+                    let is_logical = LOGICAL_ATTRIBUTES.iter().any(|a| json.contains_key(*a));
+                    if !is_logical {
+                        return self.schema(&ty.into());
+                    }
+                    normalized.insert("type".to_string(), ty.into());
+                    copy_logical(json, &mut normalized);
+                }
+            }
+            Ok(normalized.into())
+        }
+
+        // Refers to a named type by its name if it is in the enclosing namespace, or by its fullname.
+        fn reference(&self, name: &Name) -> JsonValue {
+            if name.namespace().is_none() || name.namespace() == self.namespace.as_deref() {
+                name.name.clone().into()
+            } else {
+                name.fullname().into()
+            }
+        }
+    }
+
+    fn copy(from: &Map<String, JsonValue>, to: &mut Map<String, JsonValue>, key: &str) {
+        if let Some(value) = from.get(key) {
+            to.insert(key.to_string(), value.clone());
+        }
+    }
+
+    fn copy_logical(from: &Map<String, JsonValue>, to: &mut Map<String, JsonValue>) {
+        for attribute in LOGICAL_ATTRIBUTES.iter() {
+            copy(from, to, attribute);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Protocol;
+    use crate::error::AvrowErr;
+    use crate::value::{Record, Value};
+    use std::str::FromStr;
+
+    const MAIL: &str = r##"
+    {
+        "protocol": "Mail",
+        "namespace": "org.example.mail",
+        "doc": "Sends mail",
+        "types": [
+            {"name": "Message", "type": "record", "fields": [
+                {"name": "to", "type": "string"},
+                {"name": "body", "type": "string"}
+            ]},
+            {"name": "Undeliverable", "type": "error", "fields": [{"name": "reason", "type": "string"}]},
+            {"name": "Priority", "type": "enum", "symbols": ["LOW", "HIGH"]}
+        ],
+        "messages": {
+            "send": {
+                "doc": "Sends a message",
+                "request": [
+                    {"name": "message", "type": "Message"},
+                    {"name": "priority", "type": "Priority"}
+                ],
+                "response": "string",
+                "errors": ["Undeliverable"]
+            },
+            "ping": {"request": [], "response": "null", "one-way": true}
+        }
+    }
+    "##;
+
+    fn message(to: &str) -> Value {
+        let mut message = Record::new("org.example.mail.Message");
+        message.insert("to", to).unwrap();
+        message.insert("body", "hi").unwrap();
+        Value::Record(message)
+    }
+
+    #[test]
+    fn parse_protocol() {
+        let protocol = Protocol::from_str(MAIL).unwrap();
+        assert_eq!(protocol.name(), "Mail");
+        assert_eq!(protocol.namespace(), Some("org.example.mail"));
+        assert_eq!(protocol.doc(), Some("Sends mail"));
+        assert_eq!(
+            protocol.types().collect::<Vec<_>>(),
+            vec![
+                "org.example.mail.Message",
+                "org.example.mail.Undeliverable",
+                "org.example.mail.Priority"
+            ]
+        );
+        let names: Vec<_> = protocol.messages().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["send", "ping"]);
+
+        let send = protocol.message("send").unwrap();
+        assert_eq!(send.doc(), Some("Sends a message"));
+        assert_eq!(
+            send.params().collect::<Vec<_>>(),
+            vec!["message", "priority"]
+        );
+        assert_eq!(
+            send.errors().collect::<Vec<_>>(),
+            vec!["org.example.mail.Undeliverable"]
+        );
+        assert!(!send.is_one_way());
+        assert!(protocol.message("ping").unwrap().is_one_way());
+    }
+
+    #[test]
+    fn validate_messages() {
+        let protocol = Protocol::from_str(MAIL).unwrap();
+        let priority = Value::Enum("HIGH".to_string());
+        protocol
+            .validate_request("send", &[message("a@example.org"), priority.clone()])
+            .unwrap();
+        assert!(protocol
+            .validate_request("send", &[priority.clone(), message("a@example.org")])
+            .is_err());
+        assert!(matches!(
+            protocol.validate_request("send", &[message("a@example.org")]),
+            Err(AvrowErr::RequestParamCount {
+                expected: 2,
+                found: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            protocol.validate_response("receive", &Value::Null),
+            Err(AvrowErr::MessageNotFound(_))
+        ));
+
+        protocol
+            .validate_response("send", &Value::Str("sent".to_string()))
+            .unwrap();
+        protocol
+            .validate_error("send", &Value::Str("failed".to_string()))
+            .unwrap();
+        let mut undeliverable = Record::new("org.example.mail.Undeliverable");
+        undeliverable.insert("reason", "no such user").unwrap();
+        protocol
+            .validate_error("send", &Value::Record(undeliverable))
+            .unwrap();
+        assert!(protocol
+            .validate_error("send", &message("a@example.org"))
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_protocols() {
+        let invalid = [
+            // missing protocol name
+            r##"{"messages": {}}"##,
+            // types must be named
+            r##"{"protocol": "P", "types": ["string"]}"##,
+            // undefined type in a message
+            r##"{"protocol": "P", "messages": {"m": {"request": [], "response": "Missing"}}}"##,
+            // errors must be declared error types
+            r##"{"protocol": "P", "types": [{"name": "R", "type": "record", "fields": []}],
+                "messages": {"m": {"request": [], "response": "null", "errors": ["R"]}}}"##,
+            // one-way messages have a null response
+            r##"{"protocol": "P", "messages": {"m": {"request": [], "response": "int", "one-way": true}}}"##,
+        ];
+        for protocol in invalid.iter() {
+            assert!(Protocol::from_str(protocol).is_err(), "{}", protocol);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn protocol_md5_fingerprint() {
+        let protocol = Protocol::from_str(MAIL).unwrap();
+        // the json written by the Java implementation's Protocol.toString()
+        let java_json = r##"{"protocol":"Mail","namespace":"org.example.mail","doc":"Sends mail","types":[{"type":"record","name":"Message","fields":[{"name":"to","type":"string"},{"name":"body","type":"string"}]},{"type":"error","name":"Undeliverable","fields":[{"name":"reason","type":"string"}]},{"type":"enum","name":"Priority","symbols":["LOW","HIGH"]}],"messages":{"send":{"doc":"Sends a message","request":[{"name":"message","type":"Message"},{"name":"priority","type":"Priority"}],"response":"string","errors":["Undeliverable"]},"ping":{"request":[],"response":"null","one-way":true}}}"##;
+        assert_eq!(protocol.normalized.to_string(), java_json);
+        let hex: String = protocol
+            .md5()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(hex, "a55ade8b279dc123ad6c580acdbb80b4");
+
+        // key order, fullnames, extra attributes and whitespace don't change the hash
+        let reordered = Protocol::from_str(
+            r##"{
+            "messages": {
+                "send": {
+                    "errors": ["Undeliverable"],
+                    "response": {"type": "string"},
+                    "request": [
+                        {"type": "Message", "name": "message"},
+                        {"type": "Priority", "name": "priority", "order": "ascending"}
+                    ],
+                    "doc": "Sends a message"
+                },
+                "ping": {"one-way": true, "response": "null", "request": []}
+            },
+            "types": [
+                {"fields": [{"type": "string", "name": "to"}, {"name": "body", "type": "string"}],
+                 "type": "record", "name": "org.example.mail.Message", "owner": "mail-team"},
+                {"type": "error", "name": "Undeliverable", "fields": [{"name": "reason", "type": "string"}]},
+                {"symbols": ["LOW", "HIGH"], "name": "Priority", "type": "enum"}
+            ],
+            "doc": "Sends mail",
+            "version": 2,
+            "protocol": "Mail",
+            "namespace": "org.example.mail"
+        }"##,
+        )
+        .unwrap();
+        assert_eq!(reordered.md5(), protocol.md5());
+    }
+}
@@ -1,8 +1,12 @@
-use crate::codec::Codec;
+use crate::codec::{BlockCompression, Codec, CodecRegistry};
 use crate::config::DEFAULT_FLUSH_INTERVAL;
 use crate::error;
+use crate::logical::{duration_from_raw, uuid_from_raw, Decimal};
+use crate::pool::WorkerPool;
+use crate::resolution::ResolutionPlan;
 use crate::schema;
 use crate::serde_avro;
+use crate::store::SchemaStore;
 use crate::util::{decode_bytes, decode_string};
 use crate::value;
 use byteorder::LittleEndian;
@@ -16,23 +20,35 @@ use schema::Variant;
 use serde::Deserialize;
 use serde_avro::SerdeReader;
 use std::collections::HashMap;
-use std::convert::TryFrom;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
 use std::str;
 use std::str::FromStr;
+use std::sync::Arc;
 use value::{FieldValue, Record, Value};
 
 /// Reader is the primary interface for reading data from an avro datafile.
 pub struct Reader<'a, R> {
     source: R,
     header: Header,
-    reader_schema: Option<&'a Schema>,
+    // plan for resolving the writer's schema to the reader's schema, if one was given
+    plan: Option<Arc<ResolutionPlan>>,
+    reader_schema: PhantomData<&'a Schema>,
     block_buffer: Cursor<Vec<u8>>,
     entries_in_block: u64,
+    pool: Option<DecodePool>,
+    max_blocks_in_flight: usize,
+    ordered: bool,
+    // values of the last block decoded by the workers
+    decoded: std::vec::IntoIter<Result<Value, AvrowErr>>,
+    end_of_file: bool,
 }
 
+// Workers decoding blocks in parallel, which take a block with its datum count and yield its values.
+type DecodePool = WorkerPool<(u64, Vec<u8>), Vec<Result<Value, AvrowErr>>>;
+
 impl<'a, R> Reader<'a, R>
 where
     R: Read,
@@ -40,38 +56,69 @@ where
     /// Creates a Reader from an avro encoded readable buffer.
     pub fn new(mut source: R) -> Result<Self, AvrowErr> {
         let header = Header::from_reader(&mut source)?;
-        Ok(Reader {
+        Ok(Reader::from_header(source, header, None))
+    }
+
+    fn from_header(source: R, header: Header, plan: Option<ResolutionPlan>) -> Self {
+        Reader {
             source,
             header,
-            reader_schema: None,
+            plan: plan.map(Arc::new),
+            reader_schema: PhantomData,
             block_buffer: Cursor::new(vec![0u8; DEFAULT_FLUSH_INTERVAL]),
             entries_in_block: 0,
-        })
+            pool: None,
+            max_blocks_in_flight: 1,
+            ordered: true,
+            decoded: vec![].into_iter(),
+            end_of_file: false,
+        }
+    }
+
+    // Starts the workers that decode blocks in parallel.
+    fn decode_in_parallel(&mut self, threads: usize, max_blocks_in_flight: usize, ordered: bool) {
+        let codec = self.header.codec.clone();
+        let schema = self.header.schema.clone();
+        let plan = self.plan.clone();
+        self.pool = Some(WorkerPool::new(
+            threads,
+            move |(entries, compressed): (u64, Vec<u8>)| {
+                decode_block(entries, compressed, &codec, &schema, plan.as_deref())
+            },
+        ));
+        self.max_blocks_in_flight = max_blocks_in_flight.max(1);
+        self.ordered = ordered;
+    }
+
+    /// Creates a Reader that looks up the writer schema in a [SchemaStore](trait.SchemaStore.html)
+    /// by the fingerprint of the schema in the datafile header. If the store has the schema,
+    /// it is used for decoding instead of the header's schema, e.g., to apply the custom logical
+    /// types registered on it. Otherwise, the header's schema is used.
+    pub fn with_store<S: SchemaStore + ?Sized>(mut source: R, store: &S) -> Result<Self, AvrowErr> {
+        let mut header = Header::from_reader(&mut source)?;
+        match store.get_by_fingerprint(header.schema.canonical_form().rabin64()) {
+            Ok(schema) => header.schema = schema,
+            Err(AvrowErr::SchemaNotInStore(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(Reader::from_header(source, header, None))
     }
 
     /// Create a Reader with the given reader schema and a readable buffer.
+    /// Returns an error if data written with the writer schema in the header can never be read
+    /// with the reader schema.
     pub fn with_schema(mut source: R, reader_schema: &'a Schema) -> Result<Self, AvrowErr> {
         let header = Header::from_reader(&mut source)?;
-
-        Ok(Reader {
-            source,
-            header,
-            reader_schema: Some(reader_schema),
-            block_buffer: Cursor::new(vec![0u8; DEFAULT_FLUSH_INTERVAL]),
-            entries_in_block: 0,
-        })
+        let plan = ResolutionPlan::new(reader_schema, &header.schema)?;
+        Ok(Reader::from_header(source, header, Some(plan)))
     }
 
     // TODO optimize based on benchmarks
     fn next_block(&mut self) -> Result<(), std::io::Error> {
-        // if no more bytes to read, read_varint below returns an EOF
-        let entries_in_block: i64 = self.source.read_varint()?;
-        self.entries_in_block = entries_in_block as u64;
-
-        let block_stream_len: i64 = self.source.read_varint()?;
-
-        let mut compressed_block = vec![0u8; block_stream_len as usize];
-        self.source.read_exact(&mut compressed_block)?;
+        let (entries_in_block, compressed_block) =
+            read_block(&mut self.source, &self.header.sync_marker)?;
+        self.entries_in_block = entries_in_block;
 
         self.header
             .codec
@@ -85,19 +132,44 @@ where
 
         // Ready for reading from block
         self.block_buffer.set_position(0);
+        Ok(())
+    }
 
-        let mut sync_marker_buf = [0u8; 16];
-        let _ = self.source.read_exact(&mut sync_marker_buf);
-
-        if sync_marker_buf != self.header.sync_marker {
-            let err = Error::new(
-                ErrorKind::Other,
-                "Sync marker does not match as expected while reading",
-            );
-            return Err(err);
+    // Yields the next value decoded by the workers, reading blocks ahead until the maximum number
+    // of blocks is in flight.
+    fn next_decoded(&mut self) -> Option<Result<Value, AvrowErr>> {
+        loop {
+            if let Some(value) = self.decoded.next() {
+                return Some(value);
+            }
+            let pool = self.pool.as_mut()?;
+            while !self.end_of_file && pool.in_flight() < self.max_blocks_in_flight {
+                match read_block(&mut self.source, &self.header.sync_marker) {
+                    Ok(block) => {
+                        if let Err(e) = pool.submit(block) {
+                            return Some(Err(e));
+                        }
+                    }
+                    Err(e) => {
+                        self.end_of_file = true;
+                        // marks the end of the avro datafile
+                        if e.kind() != ErrorKind::UnexpectedEof {
+                            return Some(Err(AvrowErr::DecodeFailed(e)));
+                        }
+                    }
+                }
+            }
+            let values = if self.ordered {
+                pool.next_in_order()
+            } else {
+                pool.next_finished()
+            };
+            match values {
+                Ok(Some(values)) => self.decoded = values.into_iter(),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
         }
-
-        Ok(())
     }
 
     /// Retrieves a reference to the header metadata map.
@@ -125,6 +197,9 @@ impl<'a, 's, R: Read> Iterator for Reader<'_, R> {
     type Item = Result<Value, AvrowErr>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.pool.is_some() {
+            return self.next_decoded();
+        }
         // invariant: True on start and end of an avro datafile
         if self.entries_in_block == 0 {
             if let Err(e) = self.next_block() {
@@ -138,20 +213,15 @@ impl<'a, 's, R: Read> Iterator for Reader<'_, R> {
         }
 
         let writer_schema = &self.header.schema;
-        let w_cxt = &writer_schema.cxt;
-        let reader_schema = &self.reader_schema;
-        let value = if let Some(r_schema) = reader_schema {
-            let r_cxt = &r_schema.cxt;
-            decode_with_resolution(
-                &r_schema.variant,
+        let value = if let Some(plan) = &self.plan {
+            plan.decode(&mut self.block_buffer)
+        } else {
+            // decode without the reader schema
+            decode(
                 &writer_schema.variant,
-                &r_cxt,
-                &w_cxt,
                 &mut self.block_buffer,
+                &writer_schema.cxt,
             )
-        } else {
-            // decode without the reader schema
-            decode(&writer_schema.variant, &mut self.block_buffer, &w_cxt)
         };
 
         self.entries_in_block -= 1;
@@ -164,322 +234,97 @@ impl<'a, 's, R: Read> Iterator for Reader<'_, R> {
     }
 }
 
-// Reads places priority on reader's schema when passing any schema context if a reader schema is provided.
-pub(crate) fn decode_with_resolution<R: Read>(
-    r_schema: &Variant,
-    w_schema: &Variant,
-    r_cxt: &Registry,
-    w_cxt: &Registry,
-    reader: &mut R,
-) -> Result<Value, AvrowErr> {
-    // LHS: Writer schema, RHS: Reader schema
-    let value = match (w_schema, r_schema) {
-        (Variant::Null, Variant::Null) => Value::Null,
-        (Variant::Boolean, Variant::Boolean) => {
-            let mut buf = [0u8; 1];
-            reader
-                .read_exact(&mut buf)
-                .map_err(AvrowErr::DecodeFailed)?;
-            match buf {
-                [0x00] => Value::Boolean(false),
-                [0x01] => Value::Boolean(true),
-                _o => {
-                    return Err(AvrowErr::DecodeFailed(Error::new(
-                        ErrorKind::InvalidData,
-                        "expecting a 0x00 or 0x01 as a byte for boolean value",
-                    )))
-                }
-            }
-        }
-        (Variant::Int, Variant::Int) => {
-            Value::Int(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
-        }
-        // int is promotable to long, float, or double (we read as int and cast to promotable.)
-        (Variant::Int, Variant::Long) => Value::Long(
-            reader
-                .read_varint::<i32>()
-                .map_err(AvrowErr::DecodeFailed)? as i64,
-        ),
-        (Variant::Int, Variant::Float) => Value::Float(
-            reader
-                .read_varint::<i32>()
-                .map_err(AvrowErr::DecodeFailed)? as f32,
-        ),
-        (Variant::Int, Variant::Double) => Value::Double(
-            reader
-                .read_varint::<i32>()
-                .map_err(AvrowErr::DecodeFailed)? as f64,
-        ),
-        (Variant::Long, Variant::Long) => {
-            Value::Long(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
-        }
-        // long is promotable to float or double
-        (Variant::Long, Variant::Float) => Value::Float(
-            reader
-                .read_varint::<i64>()
-                .map_err(AvrowErr::DecodeFailed)? as f32,
-        ),
-        (Variant::Long, Variant::Double) => Value::Double(
-            reader
-                .read_varint::<i64>()
-                .map_err(AvrowErr::DecodeFailed)? as f64,
-        ),
-        (Variant::Float, Variant::Float) => Value::Float(
-            reader
-                .read_f32::<LittleEndian>()
-                .map_err(AvrowErr::DecodeFailed)?,
-        ),
-        (Variant::Double, Variant::Double) => Value::Double(
-            reader
-                .read_f64::<LittleEndian>()
-                .map_err(AvrowErr::DecodeFailed)?,
-        ),
-        // float is promotable to double
-        (Variant::Float, Variant::Double) => Value::Double(
-            reader
-                .read_f32::<LittleEndian>()
-                .map_err(AvrowErr::DecodeFailed)? as f64,
-        ),
-        (Variant::Bytes, Variant::Bytes) => Value::Bytes(decode_bytes(reader)?),
-        // bytes is promotable to string
-        (Variant::Bytes, Variant::Str) => {
-            let bytes = decode_bytes(reader)?;
-            let s = str::from_utf8(&bytes).map_err(|_e| {
-                let err = Error::new(ErrorKind::InvalidData, "failed converting bytes to string");
-                AvrowErr::DecodeFailed(err)
-            })?;
-
-            Value::Str(s.to_string())
-        }
-        (Variant::Str, Variant::Str) => {
-            let buf = decode_bytes(reader)?;
-            let s = str::from_utf8(&buf).map_err(|_e| {
-                let err = Error::new(ErrorKind::InvalidData, "failed converting bytes to string");
-                AvrowErr::DecodeFailed(err)
-            })?;
-            Value::Str(s.to_string())
-        }
-        // string is promotable to bytes
-        (Variant::Str, Variant::Bytes) => {
-            let buf = decode_bytes(reader)?;
-            Value::Bytes(buf)
-        }
-        (Variant::Array { items: w_items }, Variant::Array { items: r_items }) => {
-            if w_items == r_items {
-                let block_count: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-                let mut v = Vec::with_capacity(block_count as usize);
-
-                for _ in 0..block_count {
-                    let decoded =
-                        decode_with_resolution(&*r_items, &*w_items, r_cxt, w_cxt, reader)?;
-                    v.push(decoded);
-                }
-
-                Value::Array(v)
-            } else {
-                return Err(AvrowErr::ArrayItemsMismatch);
-            }
-        }
-        // Resolution rules
-        // if both are records:
-        // * The ordering of fields may be different: fields are matched by name. [1]
-        // * Schemas for fields with the same name in both records are resolved recursively. [2]
-        // * If the writer's record contains a field with a name not present in the reader's record,
-        //   the writer's value for that field is ignored. [3]
-        // * If the reader's record schema has a field that contains a default value,
-        //   and writer's schema does not have a field with the same name,
-        //   then the reader should use the default value from its field. [4]
-        // * If the reader's record schema has a field with no default value,
-        //   and writer's schema does not have a field with the same name, an error is signalled. [5]
-        (
-            Variant::Record {
-                name: writer_name,
-                fields: writer_fields,
-                ..
-            },
-            Variant::Record {
-                name: reader_name,
-                fields: reader_fields,
-                ..
-            },
-        ) => {
-            // [1]
-            let reader_name = reader_name.fullname();
-            let writer_name = writer_name.fullname();
-            if writer_name != reader_name {
-                return Err(AvrowErr::RecordNameMismatch);
-            }
-
-            let mut rec = Record::new(&reader_name);
-            for f in reader_fields {
-                let reader_fieldname = f.0.as_str();
-                let reader_field = f.1;
-                // [3]
-                if let Some(wf) = writer_fields.get(reader_fieldname) {
-                    // [2]
-                    let f_decoded =
-                        decode_with_resolution(&reader_field.ty, &wf.ty, r_cxt, w_cxt, reader)?;
-                    rec.insert(&reader_fieldname, f_decoded)?;
-                } else {
-                    // [4]
-                    let default_field = f.1;
-                    if let Some(a) = &default_field.default {
-                        rec.insert(&reader_fieldname, a.clone())?;
-                    } else {
-                        // [5]
-                        return Err(AvrowErr::FieldNotFound);
-                    }
-                }
-            }
-
-            return Ok(Value::Record(rec));
-        }
-        (
-            Variant::Enum {
-                name: w_name,
-                symbols: w_symbols,
-                ..
-            },
-            Variant::Enum {
-                name: r_name,
-                symbols: r_symbols,
-                ..
-            },
-        ) => {
-            if w_name.fullname() != r_name.fullname() {
-                return Err(AvrowErr::EnumNameMismatch);
-            }
-
-            let idx: i32 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-            let idx = idx as usize;
-            if idx >= w_symbols.len() {
-                return Err(AvrowErr::InvalidEnumSymbolIdx(
-                    idx,
-                    format!("{:?}", w_symbols),
-                ));
-            }
-
-            let symbol = r_symbols.get(idx as usize);
-            if let Some(s) = symbol {
-                return Ok(Value::Enum(s.to_string()));
-            } else {
-                return Err(AvrowErr::EnumSymbolNotFound { idx });
-            }
-        }
-        (
-            Variant::Fixed {
-                name: w_name,
-                size: w_size,
-            },
-            Variant::Fixed {
-                name: r_name,
-                size: r_size,
-            },
-        ) => {
-            if w_name.fullname() != r_name.fullname() && w_size != r_size {
-                return Err(AvrowErr::FixedSchemaNameMismatch);
-            } else {
-                let mut fixed = vec![0u8; *r_size];
-                reader
-                    .read_exact(&mut fixed)
-                    .map_err(AvrowErr::DecodeFailed)?;
-                Value::Fixed(fixed)
-            }
-        }
-        (
-            Variant::Map {
-                values: writer_values,
-            },
-            Variant::Map {
-                values: reader_values,
-            },
-        ) => {
-            // here equality will be based
-            if writer_values == reader_values {
-                let block_count: i32 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-                let mut hm = HashMap::new();
-                for _ in 0..block_count {
-                    let key = decode_string(reader)?;
-                    let value = decode(reader_values, reader, r_cxt)?;
-                    hm.insert(key, value);
-                }
-                Value::Map(hm)
-            } else {
-                return Err(AvrowErr::MapSchemaMismatch);
-            }
-        }
-        (
-            Variant::Union {
-                variants: writer_variants,
-            },
-            Variant::Union {
-                variants: reader_variants,
-            },
-        ) => {
-            let union_idx: i32 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-            if let Some(writer_schema) = writer_variants.get(union_idx as usize) {
-                for i in reader_variants {
-                    if i == writer_schema {
-                        return decode(i, reader, r_cxt);
-                    }
-                }
-            }
+// Reads the datum count and the compressed data of the next block of a datafile, checking the sync marker
+// that follows it. Fails with an `UnexpectedEof` error at the end of the datafile.
+pub(crate) fn read_block<R: Read>(
+    source: &mut R,
+    sync_marker: &[u8; 16],
+) -> Result<(u64, Vec<u8>), Error> {
+    // if no more bytes to read, read_varint below returns an EOF
+    let entries_in_block: i64 = source.read_varint()?;
+    let block_stream_len: i64 = source.read_varint()?;
+
+    let mut compressed_block = vec![0u8; block_stream_len as usize];
+    source.read_exact(&mut compressed_block)?;
+
+    let mut sync_marker_buf = [0u8; 16];
+    let _ = source.read_exact(&mut sync_marker_buf);
+
+    if &sync_marker_buf != sync_marker {
+        let err = Error::new(
+            ErrorKind::Other,
+            "Sync marker does not match as expected while reading",
+        );
+        return Err(err);
+    }
+    Ok((entries_in_block as u64, compressed_block))
+}
 
-            return Err(AvrowErr::UnionSchemaMismatch);
+// Decompresses a block and decodes its values with the writer's schema, or with the resolution plan if
+// one is given. Decoding stops at the first value that fails to decode.
+pub(crate) fn decode_block(
+    entries: u64,
+    compressed: Vec<u8>,
+    codec: &BlockCompression,
+    schema: &Schema,
+    plan: Option<&ResolutionPlan>,
+) -> Vec<Result<Value, AvrowErr>> {
+    let mut block = Cursor::new(vec![]);
+    if let Err(e) = codec.decode(compressed, &mut block) {
+        return vec![Err(e)];
+    }
+    block.set_position(0);
+    // the count comes from the datafile, so it's not trusted beyond the size of the block
+    let mut values = Vec::with_capacity((entries as usize).min(block.get_ref().len()));
+    for _ in 0..entries {
+        let value = match plan {
+            Some(plan) => plan.decode(&mut block),
+            None => decode(&schema.variant, &mut block, &schema.cxt),
+        };
+        let failed = value.is_err();
+        values.push(value);
+        // the rest of the block cannot be decoded
+        if failed {
+            break;
         }
-        /*
-         if reader's is a union but writer's is not. The first schema in the reader's union that matches
-         the writer's schema is recursively resolved against it. If none match, an error is signalled.
-        */
-        (
-            writer_schema,
-            Variant::Union {
-                variants: reader_variants,
-            },
-        ) => {
-            for i in reader_variants {
-                if i == writer_schema {
-                    return decode(i, reader, r_cxt);
-                }
-            }
+    }
+    values
+}
 
-            return Err(AvrowErr::WriterNotInReader);
+// Reads the blocks of an array or a map, decoding each item with `decode_item` until the
+// terminating block with a count of zero is read. A negative count is followed by the size
+// of the block in bytes, which is not needed when decoding the items.
+pub(crate) fn decode_blocks<R: Read, F: FnMut(&mut R) -> Result<(), AvrowErr>>(
+    reader: &mut R,
+    mut decode_item: F,
+) -> Result<(), AvrowErr> {
+    loop {
+        let mut block_count: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+        if block_count == 0 {
+            return Ok(());
+        } else if block_count < 0 {
+            let _block_size: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            block_count = block_count.checked_neg().ok_or_else(|| {
+                AvrowErr::DecodeFailed(Error::new(
+                    ErrorKind::InvalidData,
+                    "Block count is out of range",
+                ))
+            })?;
         }
-        /*
-         if writer's schema is a union, but reader's is not.
-         If the reader's schema matches the selected writer's schema,
-         it is recursively resolved against it. If they do not match, an error is signalled.
-        */
-        (
-            Variant::Union {
-                variants: writer_variants,
-            },
-            reader_schema,
-        ) => {
-            // Read the index value in the schema
-            let union_idx: i32 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-            let schema = writer_variants.get(union_idx as usize);
-            if let Some(s) = schema {
-                if s == reader_schema {
-                    return decode(reader_schema, reader, r_cxt);
-                }
-            }
-            let writer_schema = format!("writer schema: {:?}", writer_variants);
-            let reader_schema = format!("reader schema: {:?}", reader_schema);
-            return Err(AvrowErr::SchemaResolutionFailed(
-                reader_schema,
-                writer_schema,
-            ));
-        }
-        other => {
-            return Err(AvrowErr::SchemaResolutionFailed(
-                format!("{:?}", other.0),
-                format!("{:?}", other.1),
-            ))
+        for _ in 0..block_count {
+            decode_item(reader)?;
         }
-    };
+    }
+}
 
-    Ok(value)
+pub(crate) fn decimal_from_raw(raw: Value, scale: usize) -> Result<Value, AvrowErr> {
+    match raw {
+        Value::Bytes(b) | Value::Fixed(b) => Ok(Value::Decimal(Decimal::from_be_bytes(&b, scale))),
+        other => Err(AvrowErr::DecodeFailed(Error::new(
+            ErrorKind::InvalidData,
+            format!("Expected bytes or fixed for a decimal, found {:?}", other),
+        ))),
+    }
 }
 
 pub(crate) fn decode<R: Read>(
@@ -529,31 +374,21 @@ pub(crate) fn decode<R: Read>(
             Value::Str(s.to_string())
         }
         Variant::Array { items } => {
-            let block_count: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-
-            if block_count == 0 {
-                // FIXME do we send an empty array?
-                return Ok(Value::Array(Vec::new()));
-            }
-
-            let mut it = Vec::with_capacity(block_count as usize);
-            for _ in 0..block_count {
-                let decoded = decode(&**items, reader, w_cxt)?;
-                it.push(decoded);
-            }
-
+            let mut it = vec![];
+            decode_blocks(reader, |reader| {
+                it.push(decode(items, reader, w_cxt)?);
+                Ok(())
+            })?;
             Value::Array(it)
         }
         Variant::Bytes => Value::Bytes(decode_bytes(reader)?),
         Variant::Map { values } => {
-            let block_count: usize = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
             let mut hm = HashMap::new();
-            for _ in 0..block_count {
+            decode_blocks(reader, |reader| {
                 let key = decode_string(reader)?;
-                let value = decode(values, reader, w_cxt)?;
-                hm.insert(key, value);
-            }
-
+                hm.insert(key, decode(values, reader, w_cxt)?);
+                Ok(())
+            })?;
             Value::Map(hm)
         }
         Variant::Record { name, fields, .. } => {
@@ -571,6 +406,16 @@ pub(crate) fn decode<R: Read>(
             };
             Value::Record(rec)
         }
+        Variant::Enum { symbols, .. } => {
+            let idx: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            let symbol = symbols.get(idx as usize).ok_or_else(|| {
+                AvrowErr::DecodeFailed(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Enum index {} out of range for {:?}", idx, symbols),
+                ))
+            })?;
+            Value::Enum(symbol.to_string())
+        }
         Variant::Fixed { size, .. } => {
             let mut buf = vec![0; *size];
             reader
@@ -588,22 +433,231 @@ pub(crate) fn decode<R: Read>(
                 .ok_or(AvrowErr::NamedSchemaNotFound)?;
             decode(schema_variant, reader, w_cxt)?
         }
-        a => {
-            return Err(AvrowErr::DecodeFailed(Error::new(
-                ErrorKind::InvalidData,
-                format!("Read failed for schema {:?}", a),
-            )))
+        Variant::Decimal { scale, inner, .. } => {
+            decimal_from_raw(decode(inner, reader, w_cxt)?, *scale)?
+        }
+        Variant::Custom { logical, inner } => {
+            logical.0.to_logical(decode(inner, reader, w_cxt)?)?
+        }
+        Variant::Uuid { inner } => uuid_from_raw(decode(inner, reader, w_cxt)?)?,
+        Variant::Duration { inner } => duration_from_raw(decode(inner, reader, w_cxt)?)?,
+        Variant::Date => Value::Date(reader.read_varint().map_err(AvrowErr::DecodeFailed)?),
+        Variant::TimeMillis => {
+            Value::TimeMillis(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
+        }
+        Variant::TimeMicros => {
+            Value::TimeMicros(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
+        }
+        Variant::TimestampMillis => {
+            Value::TimestampMillis(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
+        }
+        Variant::TimestampMicros => {
+            Value::TimestampMicros(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
+        }
+        Variant::LocalTimestampMillis => {
+            Value::LocalTimestampMillis(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
+        }
+        Variant::LocalTimestampMicros => {
+            Value::LocalTimestampMicros(reader.read_varint().map_err(AvrowErr::DecodeFailed)?)
         }
     };
 
     Ok(value)
 }
 
+// Advances the reader past a value of the given schema without decoding it into a Value.
+// Arrays and maps written with their size in bytes are skipped block by block.
+pub(crate) fn skip<R: Read>(
+    schema: &Variant,
+    reader: &mut R,
+    cxt: &Registry,
+) -> Result<(), AvrowErr> {
+    match schema {
+        Variant::Null => {}
+        Variant::Boolean => skip_bytes(reader, 1)?,
+        Variant::Int
+        | Variant::Long
+        | Variant::Enum { .. }
+        | Variant::Date
+        | Variant::TimeMillis
+        | Variant::TimeMicros
+        | Variant::TimestampMillis
+        | Variant::TimestampMicros
+        | Variant::LocalTimestampMillis
+        | Variant::LocalTimestampMicros => {
+            let _: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+        }
+        Variant::Float => skip_bytes(reader, 4)?,
+        Variant::Double => skip_bytes(reader, 8)?,
+        Variant::Bytes | Variant::Str => {
+            let len: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            skip_bytes(reader, len as u64)?;
+        }
+        Variant::Fixed { size, .. } => skip_bytes(reader, *size as u64)?,
+        Variant::Record { fields, .. } => {
+            for field in fields.values() {
+                skip(&field.ty, reader, cxt)?;
+            }
+        }
+        Variant::Array { items } => skip_blocks(reader, |reader| skip(items, reader, cxt))?,
+        Variant::Map { values } => skip_blocks(reader, |reader| {
+            let len: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            skip_bytes(reader, len as u64)?;
+            skip(values, reader, cxt)
+        })?,
+        Variant::Union { variants } => {
+            let idx: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            let variant = variants.get(idx as usize).ok_or_else(|| {
+                AvrowErr::DecodeFailed(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Union index {} out of range for {:?}", idx, variants),
+                ))
+            })?;
+            skip(variant, reader, cxt)?;
+        }
+        Variant::Named(name) => {
+            let schema = cxt.get(name).ok_or(AvrowErr::NamedSchemaNotFound)?;
+            skip(schema, reader, cxt)?;
+        }
+        Variant::Decimal { inner, .. }
+        | Variant::Uuid { inner }
+        | Variant::Duration { inner }
+        | Variant::Custom { inner, .. } => skip(inner, reader, cxt)?,
+    }
+    Ok(())
+}
+
+// Skips the blocks of an array or a map. Blocks with a negative count are followed by their
+// size in bytes and are skipped without looking at their items.
+fn skip_blocks<R: Read, F: FnMut(&mut R) -> Result<(), AvrowErr>>(
+    reader: &mut R,
+    mut skip_item: F,
+) -> Result<(), AvrowErr> {
+    loop {
+        let block_count: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+        if block_count == 0 {
+            return Ok(());
+        } else if block_count < 0 {
+            let block_size: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+            skip_bytes(reader, block_size as u64)?;
+        } else {
+            for _ in 0..block_count {
+                skip_item(reader)?;
+            }
+        }
+    }
+}
+
+fn skip_bytes<R: Read>(reader: &mut R, len: u64) -> Result<(), AvrowErr> {
+    let skipped = std::io::copy(&mut reader.take(len), &mut std::io::sink())
+        .map_err(AvrowErr::DecodeFailed)?;
+    if skipped != len {
+        return Err(AvrowErr::DecodeFailed(Error::new(
+            ErrorKind::UnexpectedEof,
+            "Unexpected end of data while skipping a value",
+        )));
+    }
+    Ok(())
+}
+
+/// Convenient builder struct for configuring and instantiating a Reader.
+pub struct ReaderBuilder<'a, R> {
+    source: Option<R>,
+    schema: Option<&'a Schema>,
+    codecs: CodecRegistry,
+    decoding_threads: usize,
+    max_blocks_in_flight: Option<usize>,
+    ordered: bool,
+}
+
+impl<'a, R: Read> ReaderBuilder<'a, R> {
+    /// Creates a builder instance to construct a Reader.
+    pub fn new() -> Self {
+        ReaderBuilder {
+            source: None,
+            schema: None,
+            codecs: CodecRegistry::new(),
+            decoding_threads: 0,
+            max_blocks_in_flight: None,
+            ordered: true,
+        }
+    }
+
+    /// Set the underlying input stream. This can be any type that implements the `Read` trait.
+    pub fn set_source(mut self, source: R) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Set the reader schema, to which values are resolved from the writer schema in the datafile header.
+    pub fn set_schema(mut self, schema: &'a Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Set the registry of user defined codecs. Codecs in the datafile header that are not built in
+    /// are looked up in this registry.
+    pub fn set_codec_registry(mut self, codecs: CodecRegistry) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Decompress and decode blocks on a pool of `threads` worker threads, while the reader reads
+    /// the following blocks from the source. This pays off for large datafiles with slow codecs.
+    /// Defaults to `0`, which decodes blocks sequentially on the thread iterating the reader.
+    pub fn set_parallel_decoding(mut self, threads: usize) -> Self {
+        self.decoding_threads = threads;
+        self
+    }
+
+    /// Set the maximum number of blocks read ahead and being decoded at a time with
+    /// [`set_parallel_decoding`](struct.ReaderBuilder.html#method.set_parallel_decoding),
+    /// which bounds the memory used by the reader. Defaults to twice the number of threads.
+    pub fn set_max_blocks_in_flight(mut self, blocks: usize) -> Self {
+        self.max_blocks_in_flight = Some(blocks);
+        self
+    }
+
+    /// Set whether values decoded in parallel are yielded in the order of the datafile. Otherwise
+    /// the values of a block are yielded as soon as the block is decoded, which keeps all workers busy.
+    /// Values within a block are always in order. Defaults to `true`.
+    pub fn set_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Builds the `Reader` instance consuming this builder. This reads the header of the datafile.
+    pub fn build(self) -> Result<Reader<'a, R>, AvrowErr> {
+        let mut source = self.source.ok_or(AvrowErr::ReaderBuildFailed)?;
+        let header = Header::with_codecs(&mut source, &self.codecs)?;
+        let plan = match self.schema {
+            Some(schema) => Some(ResolutionPlan::new(schema, &header.schema)?),
+            None => None,
+        };
+        let mut reader = Reader::from_header(source, header, plan);
+        if self.decoding_threads > 0 {
+            reader.decode_in_parallel(
+                self.decoding_threads,
+                self.max_blocks_in_flight
+                    .unwrap_or(2 * self.decoding_threads),
+                self.ordered,
+            );
+        }
+        Ok(reader)
+    }
+}
+
+impl<'a, R: Read> Default for ReaderBuilder<'a, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Header represents the avro datafile header.
 #[derive(Debug)]
 pub struct Header {
     /// Writer's schema
-    pub(crate) schema: Schema,
+    pub(crate) schema: Arc<Schema>,
     /// A Map which stores avro metadata, like `avro.codec` and `avro.schema`.
     /// Additional key values can be added through the
     /// [WriterBuilder](struct.WriterBuilder.html)'s `set_metadata` method.
@@ -611,31 +665,34 @@ pub struct Header {
     /// A unique 16 byte sequence for file integrity when writing avro data to file.
     pub(crate) sync_marker: [u8; 16],
     /// codec parsed from the datafile
-    pub(crate) codec: Codec,
+    pub(crate) codec: BlockCompression,
 }
 
 fn decode_header_map<R>(reader: &mut R) -> Result<HashMap<String, Vec<u8>>, AvrowErr>
 where
     R: Read,
 {
-    let count: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-    let count = count as usize;
-    let mut map = HashMap::with_capacity(count);
-
-    for _ in 0..count {
+    let mut map = HashMap::new();
+    decode_blocks(reader, |reader| {
         let key = decode_string(reader)?;
         let val = decode_bytes(reader)?;
         map.insert(key, val);
-    }
-
-    let _map_end: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
-
+        Ok(())
+    })?;
     Ok(map)
 }
 
 impl Header {
     /// Reads the header from an avro datafile
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, AvrowErr> {
+        Header::with_codecs(reader, &CodecRegistry::new())
+    }
+
+    // Reads the header, looking up codecs that are not built in in the codec registry.
+    pub(crate) fn with_codecs<R: Read>(
+        reader: &mut R,
+        codecs: &CodecRegistry,
+    ) -> Result<Self, AvrowErr> {
         let mut magic_buf = [0u8; 4];
         reader
             .read_exact(&mut magic_buf[..])
@@ -660,15 +717,15 @@ impl Header {
 
         let codec = if let Some(c) = map.get("avro.codec") {
             match std::str::from_utf8(c) {
-                Ok(s) => Codec::try_from(s)?,
+                Ok(s) => BlockCompression::from_metadata(s, &map, codecs)?,
                 Err(s) => return Err(AvrowErr::UnsupportedCodec(s.to_string())),
             }
         } else {
-            Codec::Null
+            BlockCompression::Builtin(Codec::Null)
         };
 
         let header = Header {
-            schema,
+            schema: Arc::new(schema),
             metadata: map,
             sync_marker,
             codec,
@@ -690,9 +747,10 @@ impl Header {
 
 #[cfg(test)]
 mod tests {
-    use crate::Reader;
     #[test]
+    #[cfg(feature = "deflate")]
     fn has_required_headers() {
+        use crate::Reader;
         let data = vec![
             79, 98, 106, 1, 4, 22, 97, 118, 114, 111, 46, 115, 99, 104, 101, 109, 97, 32, 123, 34,
             116, 121, 112, 101, 34, 58, 34, 98, 121, 116, 101, 115, 34, 125, 20, 97, 118, 114, 111,
@@ -706,4 +764,67 @@ mod tests {
         assert!(reader.meta().contains_key("avro.codec"));
         assert!(reader.meta().contains_key("avro.schema"));
     }
+
+    #[test]
+    fn skips_blocks_with_byte_size() {
+        use super::skip;
+        use crate::Schema;
+        use std::io::Cursor;
+        use std::str::FromStr;
+
+        let schema = Schema::from_str(
+            r##"{"type": "array", "items": {"type": "map", "values": "string"}}"##,
+        )
+        .unwrap();
+        // An array block of -1 items with a size of 7 bytes holding the map {"a": "bc"},
+        // followed by a block of 1 item holding an empty map, the end of the array and a trailing byte.
+        let data = vec![1, 14, 2, 2, 97, 4, 98, 99, 0, 2, 0, 0, 42];
+        let mut reader = Cursor::new(data);
+        skip(schema.variant(), &mut reader, &schema.cxt).unwrap();
+        assert_eq!(reader.position(), 12);
+    }
+
+    #[test]
+    fn decodes_multiple_and_sized_blocks() {
+        use super::decode;
+        use crate::{Schema, Value};
+        use std::io::Cursor;
+        use std::str::FromStr;
+
+        let schema = Schema::from_str(r##"{"type": "array", "items": "int"}"##).unwrap();
+        // A block of 2 items, a block of -1 items with a size of 1 byte and the end of the array.
+        let data = vec![4, 2, 4, 1, 2, 6, 0];
+        let value = decode(schema.variant(), &mut Cursor::new(data), &schema.cxt).unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+
+        // A block count of i64::MIN cannot be negated
+        let data = vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1, 0];
+        assert!(decode(schema.variant(), &mut Cursor::new(data), &schema.cxt).is_err());
+    }
+
+    #[test]
+    fn header_map_with_multiple_blocks() {
+        use super::Header;
+        use std::io::Cursor;
+
+        let mut data = b"Obj\x01".to_vec();
+        // {"avro.schema": "\"int\""} in a block of 1 entry
+        data.extend(&[2, 22]);
+        data.extend(b"avro.schema");
+        data.extend(&[10]);
+        data.extend(b"\"int\"");
+        // {"avro.codec": "null"} in a block of -1 entries with a size of 16 bytes
+        data.extend(&[1, 32, 20]);
+        data.extend(b"avro.codec");
+        data.extend(&[8]);
+        data.extend(b"null");
+        data.extend(&[0]);
+        data.extend(&[0u8; 16]);
+        let header = Header::from_reader(&mut Cursor::new(data)).unwrap();
+        assert_eq!(header.metadata["avro.codec"], b"null");
+        assert_eq!(header.metadata["avro.schema"], b"\"int\"");
+    }
 }
@@ -0,0 +1,333 @@
+//! Contains a client for the REST API of a [Confluent Schema Registry](https://docs.confluent.io/platform/current/schema-registry/develop/api.html).
+//! The client implements [SchemaStore](trait.SchemaStore.html) and can be used to decode messages
+//! in the Confluent wire format with [from_wire_format](fn.from_wire_format.html).
+
+use crate::error::AvrowErr;
+use crate::schema::Schema;
+use crate::store::{SchemaStore, Schemas};
+use serde_json::{json, Value as JsonValue};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// A client for a Confluent compatible schema registry. Schemas fetched from or registered with
+/// the registry are cached, so each schema id is requested at most once. Registering a schema
+/// always sends it to the registry, which returns the existing id if it's already registered.
+///
+/// ```rust,no_run
+/// use avrow::{from_wire_format, Schema, SchemaRegistryClient};
+/// use std::str::FromStr;
+///
+/// let registry = SchemaRegistryClient::new("http://localhost:8081");
+/// let reader_schema = Schema::from_str(r##""string""##).unwrap();
+/// let msg = [0, 0, 0, 0, 1, 10, 104, 101, 108, 108, 111];
+/// let value = from_wire_format(&msg, &registry, &reader_schema).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SchemaRegistryClient {
+    url: String,
+    subject: Option<String>,
+    agent: ureq::Agent,
+    cache: RwLock<Schemas>,
+}
+
+impl SchemaRegistryClient {
+    /// Creates a client for the schema registry at the given base url.
+    pub fn new(url: &str) -> Self {
+        SchemaRegistryClient {
+            url: url.trim_end_matches('/').to_string(),
+            subject: None,
+            agent: ureq::AgentBuilder::new().build(),
+            cache: RwLock::new(Schemas::default()),
+        }
+    }
+
+    /// Sets the subject under which schemas are registered via
+    /// [SchemaStore::register](trait.SchemaStore.html#tymethod.register).
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    /// Lists the subjects in the registry.
+    pub fn subjects(&self) -> Result<Vec<String>, AvrowErr> {
+        let subjects = self.get("/subjects")?;
+        serde_json::from_value(subjects).map_err(|e| AvrowErr::SchemaRegistryErr(e.to_string()))
+    }
+
+    /// Returns the id and the schema of the latest version registered under a subject.
+    pub fn latest_schema(&self, subject: &str) -> Result<(u32, Arc<Schema>), AvrowErr> {
+        let latest = self.get(&format!(
+            "/subjects/{}/versions/latest",
+            encode_path_segment(subject)
+        ))?;
+        let id = parse_id(&latest)?;
+        if let Ok(schema) = self.cached()?.get_by_id(id) {
+            return Ok((id, schema));
+        }
+        Ok((id, self.cache_schema(id, &latest)?))
+    }
+
+    /// Registers a schema under the given subject and returns its id.
+    pub fn register_with_subject(&self, subject: &str, schema: Schema) -> Result<u32, AvrowErr> {
+        let body = json!({ "schema": schema.to_string() });
+        let response = self
+            .agent
+            .post(&format!(
+                "{}/subjects/{}/versions",
+                self.url,
+                encode_path_segment(subject)
+            ))
+            .set("Content-Type", CONTENT_TYPE)
+            .set("Accept", CONTENT_TYPE)
+            .send_string(&body.to_string())
+            .map_err(|e| AvrowErr::SchemaRegistryErr(e.to_string()))?;
+        let id = parse_id(&read_json(response)?)?;
+        self.cache
+            .write()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)?
+            .insert(id, Arc::new(schema));
+        Ok(id)
+    }
+
+    fn get(&self, path: &str) -> Result<JsonValue, AvrowErr> {
+        let response = self
+            .agent
+            .get(&format!("{}{}", self.url, path))
+            .set("Accept", CONTENT_TYPE)
+            .call()
+            .map_err(|e| AvrowErr::SchemaRegistryErr(e.to_string()))?;
+        read_json(response)
+    }
+
+    fn cached(&self) -> Result<std::sync::RwLockReadGuard<'_, Schemas>, AvrowErr> {
+        self.cache.read().map_err(|_| AvrowErr::SchemaStorePoisoned)
+    }
+
+    // Parses the `schema` of a registry response and caches it under the given id.
+    fn cache_schema(&self, id: u32, response: &JsonValue) -> Result<Arc<Schema>, AvrowErr> {
+        let schema = response
+            .get("schema")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| {
+                AvrowErr::SchemaRegistryErr(format!("missing schema in {}", response))
+            })?;
+        let schema = Arc::new(Schema::from_str(schema)?);
+        self.cache
+            .write()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)?
+            .insert(id, schema.clone());
+        Ok(schema)
+    }
+}
+
+impl SchemaStore for SchemaRegistryClient {
+    fn get_by_id(&self, id: u32) -> Result<Arc<Schema>, AvrowErr> {
+        if let Ok(schema) = self.cached()?.get_by_id(id) {
+            return Ok(schema);
+        }
+        let response = self.get(&format!("/schemas/ids/{}", id))?;
+        self.cache_schema(id, &response)
+    }
+
+    // The registry has no lookup by fingerprint, so only cached schemas are found.
+    fn get_by_fingerprint(&self, fingerprint: i64) -> Result<Arc<Schema>, AvrowErr> {
+        self.cached()?.get_by_fingerprint(fingerprint)
+    }
+
+    fn register(&self, schema: Schema) -> Result<u32, AvrowErr> {
+        let subject = self.subject.as_ref().ok_or_else(|| {
+            AvrowErr::SchemaRegistryErr("no subject set to register the schema under".to_string())
+        })?;
+        self.register_with_subject(subject, schema)
+    }
+}
+
+fn read_json(response: ureq::Response) -> Result<JsonValue, AvrowErr> {
+    let body = response.into_string().map_err(AvrowErr::SchemaStoreIo)?;
+    serde_json::from_str(&body).map_err(|e| AvrowErr::SchemaRegistryErr(e.to_string()))
+}
+
+// Percent-encodes all but the unreserved characters of a url path segment.
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for b in segment.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn parse_id(response: &JsonValue) -> Result<u32, AvrowErr> {
+    response
+        .get("id")
+        .and_then(|id| id.as_u64())
+        .map(|id| id as u32)
+        .ok_or_else(|| AvrowErr::SchemaRegistryErr(format!("missing id in {}", response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SchemaRegistryClient;
+    use crate::message::{from_wire_format, to_wire_format};
+    use crate::schema::Schema;
+    use crate::store::SchemaStore;
+    use crate::value::Value;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    // Serves canned registry responses and records the requests it receives.
+    fn mock_registry() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_len = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    let lower = line.to_lowercase();
+                    if let Some(len) = lower.strip_prefix("content-length:") {
+                        content_len = len.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; content_len];
+                reader.read_exact(&mut body).unwrap();
+
+                let request_line = request_line.trim().to_string();
+                let (status, body) = match request_line.as_str() {
+                    "GET /subjects HTTP/1.1" => ("200 OK", r#"["users-value"]"#.to_string()),
+                    "GET /schemas/ids/3 HTTP/1.1" => {
+                        ("200 OK", r#"{"schema": "\"int\""}"#.to_string())
+                    }
+                    "GET /subjects/users-value/versions/latest HTTP/1.1" => (
+                        "200 OK",
+                        r#"{"subject": "users-value", "version": 2, "id": 3, "schema": "\"int\""}"#
+                            .to_string(),
+                    ),
+                    "GET /subjects/team%2Fusers%20value%3Fv%23latest/versions/latest HTTP/1.1" => (
+                        "200 OK",
+                        r#"{"subject": "team/users value?v#latest", "version": 1, "id": 3, "schema": "\"int\""}"#
+                            .to_string(),
+                    ),
+                    "POST /subjects/users-value/versions HTTP/1.1"
+                    | "POST /subjects/orders-value/versions HTTP/1.1"
+                    | "POST /subjects/team%2Fusers%20value%3Fv%23latest/versions HTTP/1.1" => {
+                        ("200 OK", r#"{"id": 4}"#.to_string())
+                    }
+                    _ => (
+                        "404 Not Found",
+                        r#"{"error_code": 40403, "message": "Schema not found"}"#.to_string(),
+                    ),
+                };
+                recorded.lock().unwrap().push(request_line);
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn registry_client_fetches_and_caches_schemas() {
+        let (url, requests) = mock_registry();
+        let registry = SchemaRegistryClient::new(&url).with_subject("users-value");
+
+        assert_eq!(
+            registry.subjects().unwrap(),
+            vec!["users-value".to_string()]
+        );
+
+        let writer_schema = Schema::from_str(r##""int""##).unwrap();
+        let msg = to_wire_format(42, 3, &writer_schema).unwrap();
+        let reader_schema = Schema::from_str(r##""long""##).unwrap();
+        for _ in 0..3 {
+            let value = from_wire_format(&msg, &registry, &reader_schema).unwrap();
+            assert_eq!(value, Value::Long(42));
+        }
+        let (id, _) = registry.latest_schema("users-value").unwrap();
+        assert_eq!(id, 3);
+        assert!(registry
+            .get_by_fingerprint(writer_schema.canonical_form().rabin64())
+            .is_ok());
+
+        let new_schema = Schema::from_str(r##""string""##).unwrap();
+        assert_eq!(registry.register(new_schema).unwrap(), 4);
+        let new_schema = Schema::from_str(r##""string""##).unwrap();
+        assert_eq!(registry.register(new_schema).unwrap(), 4);
+
+        assert!(registry.get_by_id(5).is_err());
+
+        let requests = requests.lock().unwrap();
+        let count = |r: &str| requests.iter().filter(|req| req.starts_with(r)).count();
+        assert_eq!(count("GET /schemas/ids/3"), 1);
+        assert_eq!(count("POST /subjects/users-value/versions"), 2);
+        assert_eq!(count("GET /schemas/ids/5"), 1);
+    }
+
+    #[test]
+    fn registry_client_registers_under_each_subject() {
+        let (url, requests) = mock_registry();
+        let registry = SchemaRegistryClient::new(&url);
+
+        for subject in &["users-value", "orders-value"] {
+            let schema = Schema::from_str(r##""string""##).unwrap();
+            assert_eq!(registry.register_with_subject(subject, schema).unwrap(), 4);
+        }
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            *requests,
+            vec![
+                "POST /subjects/users-value/versions HTTP/1.1".to_string(),
+                "POST /subjects/orders-value/versions HTTP/1.1".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_client_encodes_subjects() {
+        let (url, requests) = mock_registry();
+        let registry = SchemaRegistryClient::new(&url);
+        let subject = "team/users value?v#latest";
+
+        let schema = Schema::from_str(r##""string""##).unwrap();
+        assert_eq!(registry.register_with_subject(subject, schema).unwrap(), 4);
+        let (id, schema) = registry.latest_schema(subject).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(*schema, Schema::from_str(r##""int""##).unwrap());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            *requests,
+            vec![
+                "POST /subjects/team%2Fusers%20value%3Fv%23latest/versions HTTP/1.1".to_string(),
+                "GET /subjects/team%2Fusers%20value%3Fv%23latest/versions/latest HTTP/1.1"
+                    .to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Contains the schema resolution plan used when reading data with a reader schema.
+//! The plan is compiled once from the writer's and reader's schemas and then executed for each datum.
+//! Incompatibilities that would fail for every datum are reported when compiling the plan, while
+//! those that depend on the data, such as a writer's union branch the reader cannot read,
+//! are reported when such a datum is read.
+
+use crate::error::AvrowErr;
+use crate::logical::{duration_from_raw, temporal_from_raw, uuid_from_raw, CustomLogical};
+use crate::reader::{decimal_from_raw, decode, decode_blocks, skip};
+use crate::schema::common::Field;
+use crate::schema::{union_branch, Registry, Schema, Variant};
+use crate::util::decode_string;
+use crate::value::{FieldValue, Record, Value};
+use indexmap::IndexMap;
+use integer_encoding::VarIntReader;
+use std::collections::HashMap;
+use std::io::Read;
+
+// A step of the resolution plan, decoding a value written with the writer's schema into a value
+// of the reader's schema.
+#[derive(Debug)]
+enum Action {
+    // Decodes a primitive or fixed value as is
+    Read(Variant),
+    // Decodes a primitive and promotes it to the reader's type
+    Promote { from: Variant, to: Variant },
+    Array(Box<Action>),
+    Map(Box<Action>),
+    // Decodes a record with the plan at the given index of the plan's records
+    Record(usize),
+    // Maps each writer's symbol index to the reader's symbol, or to the writer's symbol if it
+    // cannot be resolved
+    Enum(Vec<Result<String, String>>),
+    // Decodes the branch at the index read from the data with the action for that branch
+    Union(Vec<Action>),
+    // Converts the decoded underlying value to a value of the reader's logical type
+    Logical(Conversion, Box<Action>),
+    // A writer's union branch that cannot be resolved against the reader's schema
+    Fail { reader: String, writer: String },
+}
+
+#[derive(Debug)]
+enum Conversion {
+    Decimal(usize),
+    Uuid,
+    Duration,
+    Temporal(Variant),
+    Custom(CustomLogical),
+}
+
+#[derive(Debug)]
+enum FieldAction {
+    // Decodes the writer's field into the reader's field at the given position
+    Read(usize, Action),
+    // Skips a writer's field that is not in the reader's record
+    Skip(Variant),
+}
+
+#[derive(Debug)]
+struct RecordPlan {
+    name: String,
+    // the reader's field names, in the reader's order
+    field_names: Vec<String>,
+    // actions in the writer's field order
+    fields: Vec<FieldAction>,
+    // defaults of the reader's fields that are not in the writer's record, by position
+    defaults: Vec<(usize, Value)>,
+}
+
+/// A precompiled plan for resolving data written with a writer schema to a reader schema.
+#[derive(Debug)]
+pub(crate) struct ResolutionPlan {
+    root: Action,
+    records: Vec<RecordPlan>,
+    w_cxt: Registry,
+}
+
+impl ResolutionPlan {
+    /// Compiles the plan to read data written with `writer` as data of `reader`.
+    pub(crate) fn new(reader: &Schema, writer: &Schema) -> Result<Self, AvrowErr> {
+        // the reader's custom logical types are only converted if the writer has them too
+        let (w_variant, w_cxt) = writer.with_logical_types_of(&reader.cxt)?;
+        let mut compiler = Compiler {
+            r_cxt: &reader.cxt,
+            w_cxt: &w_cxt,
+            records: vec![],
+            compiled: HashMap::new(),
+        };
+        let root = compiler.compile(reader.variant(), &w_variant)?;
+        let records = compiler.records;
+        Ok(ResolutionPlan {
+            root,
+            records,
+            w_cxt,
+        })
+    }
+
+    /// Decodes a single datum by executing the plan.
+    pub(crate) fn decode<R: Read>(&self, reader: &mut R) -> Result<Value, AvrowErr> {
+        self.execute(&self.root, reader)
+    }
+
+    fn execute<R: Read>(&self, action: &Action, reader: &mut R) -> Result<Value, AvrowErr> {
+        let value = match action {
+            Action::Read(schema) => decode(schema, reader, &self.w_cxt)?,
+            Action::Promote { from, to } => promote(decode(from, reader, &self.w_cxt)?, to),
+            Action::Array(items) => {
+                let mut v = vec![];
+                decode_blocks(reader, |reader| {
+                    v.push(self.execute(items, reader)?);
+                    Ok(())
+                })?;
+                Value::Array(v)
+            }
+            Action::Map(values) => {
+                let mut hm = HashMap::new();
+                decode_blocks(reader, |reader| {
+                    let key = decode_string(reader)?;
+                    hm.insert(key, self.execute(values, reader)?);
+                    Ok(())
+                })?;
+                Value::Map(hm)
+            }
+            Action::Record(idx) => {
+                let plan = &self.records[*idx];
+                let mut values: Vec<Option<Value>> = vec![None; plan.field_names.len()];
+                for field in &plan.fields {
+                    match field {
+                        FieldAction::Read(pos, action) => {
+                            values[*pos] = Some(self.execute(action, reader)?)
+                        }
+                        FieldAction::Skip(schema) => skip(schema, reader, &self.w_cxt)?,
+                    }
+                }
+                for (pos, default) in &plan.defaults {
+                    values[*pos] = Some(default.clone());
+                }
+
+                let mut fields = IndexMap::with_capacity(values.len());
+                for (name, value) in plan.field_names.iter().zip(values) {
+                    // every reader's field is either read or defaulted
+                    if let Some(value) = value {
+                        fields.insert(name.to_string(), FieldValue::new(value));
+                    }
+                }
+                Value::Record(Record {
+                    name: plan.name.to_string(),
+                    fields,
+                })
+            }
+            Action::Enum(symbols) => {
+                let idx: i32 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+                match symbols.get(idx as usize) {
+                    Some(Ok(symbol)) => Value::Enum(symbol.to_string()),
+                    Some(Err(symbol)) => {
+                        return Err(AvrowErr::EnumSymbolNotFound {
+                            symbol: symbol.to_string(),
+                        })
+                    }
+                    None => {
+                        return Err(AvrowErr::InvalidEnumSymbolIdx(
+                            idx as usize,
+                            format!("{} writer symbols", symbols.len()),
+                        ))
+                    }
+                }
+            }
+            Action::Union(branches) => {
+                let idx: i64 = reader.read_varint().map_err(AvrowErr::DecodeFailed)?;
+                let branch = branches.get(idx as usize).ok_or_else(|| {
+                    AvrowErr::SchemaResolutionFailed(
+                        format!("union branch {}", idx),
+                        format!("union of {} branches", branches.len()),
+                    )
+                })?;
+                self.execute(branch, reader)?
+            }
+            Action::Logical(conversion, inner) => {
+                let raw = self.execute(inner, reader)?;
+                match conversion {
+                    Conversion::Decimal(scale) => decimal_from_raw(raw, *scale)?,
+                    Conversion::Uuid => uuid_from_raw(raw)?,
+                    Conversion::Duration => duration_from_raw(raw)?,
+                    Conversion::Temporal(schema) => temporal_from_raw(schema, raw)?,
+                    Conversion::Custom(logical) => logical.0.to_logical(raw)?,
+                }
+            }
+            Action::Fail { reader, writer } => {
+                return Err(AvrowErr::SchemaResolutionFailed(
+                    reader.to_string(),
+                    writer.to_string(),
+                ))
+            }
+        };
+        Ok(value)
+    }
+}
+
+// Promotes a decoded primitive to the reader's type.
+fn promote(value: Value, to: &Variant) -> Value {
+    match (value, to) {
+        (Value::Int(i), Variant::Long) => Value::Long(i as i64),
+        (Value::Int(i), Variant::Float) => Value::Float(i as f32),
+        (Value::Int(i), Variant::Double) => Value::Double(i as f64),
+        (Value::Long(l), Variant::Float) => Value::Float(l as f32),
+        (Value::Long(l), Variant::Double) => Value::Double(l as f64),
+        (Value::Float(f), Variant::Double) => Value::Double(f as f64),
+        (Value::Str(s), Variant::Bytes) => Value::Bytes(s.into_bytes()),
+        (other, _) => other,
+    }
+}
+
+struct Compiler<'a> {
+    r_cxt: &'a Registry,
+    w_cxt: &'a Registry,
+    // record plans, which are empty while being compiled
+    records: Vec<RecordPlan>,
+    // indices of compiled record plans, by reader and writer record names
+    compiled: HashMap<(String, String), usize>,
+}
+
+impl<'a> Compiler<'a> {
+    fn compile(
+        &mut self,
+        r_schema: &'a Variant,
+        w_schema: &'a Variant,
+    ) -> Result<Action, AvrowErr> {
+        // As per spec, logical types are resolved using their underlying types. The reader's
+        // logical type converts the resolved underlying value only if the writer has the same
+        // logical type, otherwise the underlying value is read as is.
+        let w_logical = match w_schema {
+            Variant::Named(name) => self.w_cxt.get(name).unwrap_or(w_schema),
+            _ => w_schema,
+        };
+        let w_schema = w_schema.underlying(self.w_cxt);
+        let r_schema = match r_schema {
+            Variant::Named(name) => self.r_cxt.get(name).ok_or(AvrowErr::NamedSchemaNotFound)?,
+            _ => r_schema,
+        };
+        let conversion = match r_schema {
+            Variant::Decimal { scale, inner, .. } => Some((Conversion::Decimal(*scale), inner)),
+            Variant::Uuid { inner } => Some((Conversion::Uuid, inner)),
+            Variant::Duration { inner } => Some((Conversion::Duration, inner)),
+            Variant::Custom { logical, inner } => {
+                Some((Conversion::Custom(logical.clone()), inner))
+            }
+            _ => None,
+        };
+        if let Some((conversion, inner)) = conversion {
+            let inner = self.compile(inner, w_schema)?;
+            if !same_logical_type(r_schema, w_logical) {
+                return Ok(inner);
+            }
+            return Ok(Action::Logical(conversion, Box::new(inner)));
+        }
+        if r_schema.temporal_underlying().is_some() {
+            let inner = self.compile(r_schema.underlying(self.r_cxt), w_schema)?;
+            if !same_logical_type(r_schema, w_logical) {
+                return Ok(inner);
+            }
+            return Ok(Action::Logical(
+                Conversion::Temporal(r_schema.clone()),
+                Box::new(inner),
+            ));
+        }
+
+        let action = match (w_schema, r_schema) {
+            (Variant::Null, Variant::Null)
+            | (Variant::Boolean, Variant::Boolean)
+            | (Variant::Int, Variant::Int)
+            | (Variant::Long, Variant::Long)
+            | (Variant::Float, Variant::Float)
+            | (Variant::Double, Variant::Double)
+            | (Variant::Bytes, Variant::Bytes)
+            // bytes is promotable to string, which is decoded as a string
+            | (Variant::Bytes, Variant::Str)
+            | (Variant::Str, Variant::Str) => Action::Read(r_schema.clone()),
+            // int is promotable to long, float or double, long to float or double, float to double
+            // and string to bytes
+            (Variant::Int, Variant::Long)
+            | (Variant::Int, Variant::Float)
+            | (Variant::Int, Variant::Double)
+            | (Variant::Long, Variant::Float)
+            | (Variant::Long, Variant::Double)
+            | (Variant::Float, Variant::Double)
+            | (Variant::Str, Variant::Bytes) => Action::Promote {
+                from: w_schema.clone(),
+                to: r_schema.clone(),
+            },
+            // Array items and map values are resolved recursively
+            (Variant::Array { items: w_items }, Variant::Array { items: r_items }) => {
+                Action::Array(Box::new(self.compile(r_items, w_items)?))
+            }
+            (Variant::Map { values: w_values }, Variant::Map { values: r_values }) => {
+                Action::Map(Box::new(self.compile(r_values, w_values)?))
+            }
+            (
+                Variant::Record {
+                    name: w_name,
+                    fields: w_fields,
+                    ..
+                },
+                Variant::Record {
+                    name: r_name,
+                    aliases: r_aliases,
+                    fields: r_fields,
+                },
+            ) => {
+                let tried = r_name.with_aliases(r_aliases.as_ref());
+                if !tried.contains(&w_name.fullname()) {
+                    return Err(AvrowErr::RecordNameMismatch {
+                        writer: w_name.fullname(),
+                        tried,
+                    });
+                }
+                let key = (r_name.fullname(), w_name.fullname());
+                if let Some(idx) = self.compiled.get(&key) {
+                    return Ok(Action::Record(*idx));
+                }
+                let idx = self.records.len();
+                self.records.push(RecordPlan {
+                    name: r_name.fullname(),
+                    field_names: vec![],
+                    fields: vec![],
+                    defaults: vec![],
+                });
+                self.compiled.insert(key.clone(), idx);
+                match self.compile_record(r_name.fullname(), r_fields, w_fields) {
+                    Ok(plan) => self.records[idx] = plan,
+                    Err(e) => {
+                        // the empty plan is left unused
+                        self.compiled.remove(&key);
+                        return Err(e);
+                    }
+                }
+                Action::Record(idx)
+            }
+            (
+                Variant::Enum {
+                    name: w_name,
+                    symbols: w_symbols,
+                    ..
+                },
+                Variant::Enum {
+                    name: r_name,
+                    aliases: r_aliases,
+                    symbols: r_symbols,
+                    default: r_default,
+                },
+            ) => {
+                let tried = r_name.with_aliases(r_aliases.as_ref());
+                if !tried.contains(&w_name.fullname()) {
+                    return Err(AvrowErr::EnumNameMismatch {
+                        writer: w_name.fullname(),
+                        tried,
+                    });
+                }
+                // Symbols are resolved by name. If the writer's symbol is not in the reader's
+                // enum, the reader's default symbol is used.
+                let symbols = w_symbols
+                    .iter()
+                    .map(|s| {
+                        if r_symbols.contains(s) {
+                            Ok(s.to_string())
+                        } else {
+                            r_default.clone().ok_or_else(|| s.to_string())
+                        }
+                    })
+                    .collect();
+                Action::Enum(symbols)
+            }
+            (
+                Variant::Fixed {
+                    name: w_name,
+                    size: w_size,
+                    ..
+                },
+                Variant::Fixed {
+                    name: r_name,
+                    aliases: r_aliases,
+                    size: r_size,
+                },
+            ) => {
+                let tried = r_name.with_aliases(r_aliases.as_ref());
+                if !tried.contains(&w_name.fullname()) {
+                    return Err(AvrowErr::FixedSchemaNameMismatch {
+                        writer: w_name.fullname(),
+                        tried,
+                    });
+                } else if w_size != r_size {
+                    return Err(mismatch(r_schema, w_schema));
+                }
+                Action::Read(w_schema.clone())
+            }
+            /*
+             if writer's schema is a union, the branch selected by the writer is recursively resolved
+             against the reader's schema, which may itself be a union. Branches that cannot be
+             resolved fail when a value of that branch is read.
+            */
+            (Variant::Union { variants }, _) => {
+                let mut branches = Vec::with_capacity(variants.len());
+                for w_variant in variants {
+                    let branch = self
+                        .compile(r_schema, w_variant)
+                        .unwrap_or_else(|_| Action::Fail {
+                            reader: format!("reader schema: {:?}", r_schema),
+                            writer: format!("writer schema: {:?}", w_variant),
+                        });
+                    branches.push(branch);
+                }
+                Action::Union(branches)
+            }
+            /*
+             if reader's is a union but writer's is not. The first schema in the reader's union that matches
+             the writer's schema is recursively resolved against it. If none match, an error is signalled.
+            */
+            (_, Variant::Union { variants }) => {
+                match union_branch(variants, w_schema, self.r_cxt, self.w_cxt) {
+                    Some(idx) => self.compile(&variants[idx], w_logical)?,
+                    None => return Err(AvrowErr::WriterNotInReader),
+                }
+            }
+            _ => return Err(mismatch(r_schema, w_schema)),
+        };
+        Ok(action)
+    }
+
+    // Resolution rules
+    // if both are records:
+    // * The ordering of fields may be different: fields are matched by name or reader's field aliases. [1]
+    // * Schemas for fields with the same name in both records are resolved recursively. [2]
+    // * If the writer's record contains a field with a name not present in the reader's record,
+    //   the writer's value for that field is ignored. [3]
+    // * If the reader's record schema has a field that contains a default value,
+    //   and writer's schema does not have a field with the same name,
+    //   then the reader should use the default value from its field. [4]
+    // * If the reader's record schema has a field with no default value,
+    //   and writer's schema does not have a field with the same name, an error is signalled. [5]
+    fn compile_record(
+        &mut self,
+        name: String,
+        r_fields: &'a IndexMap<String, Field>,
+        w_fields: &'a IndexMap<String, Field>,
+    ) -> Result<RecordPlan, AvrowErr> {
+        let mut fields: Vec<Option<FieldAction>> = w_fields.values().map(|_| None).collect();
+        let mut defaults = vec![];
+        for (pos, r_field) in r_fields.values().enumerate() {
+            // [1]
+            let w_field = r_field
+                .names()
+                .into_iter()
+                .find_map(|n| w_fields.get_full(n));
+            if let Some((w_pos, _, w_field)) = w_field {
+                // [2]
+                let action = self.compile(&r_field.ty, &w_field.ty)?;
+                fields[w_pos] = Some(FieldAction::Read(pos, action));
+            } else if let Some(default) = &r_field.default {
+                // [4]
+                defaults.push((pos, default.clone()));
+            } else {
+                // [5]
+                return Err(AvrowErr::ReaderFieldNotInWriter {
+                    tried: r_field.names().iter().map(|n| n.to_string()).collect(),
+                });
+            }
+        }
+
+        // [3]
+        let fields = fields
+            .into_iter()
+            .zip(w_fields.values())
+            .map(|(action, w_field)| {
+                action.unwrap_or_else(|| FieldAction::Skip(w_field.ty.clone()))
+            })
+            .collect();
+
+        Ok(RecordPlan {
+            name,
+            field_names: r_fields.keys().cloned().collect(),
+            fields,
+            defaults,
+        })
+    }
+}
+
+// Returns true if the writer's schema has the reader's logical type. Decimals must also have the
+// same precision and scale.
+fn same_logical_type(r_schema: &Variant, w_schema: &Variant) -> bool {
+    match (r_schema, w_schema) {
+        (
+            Variant::Decimal {
+                precision, scale, ..
+            },
+            Variant::Decimal {
+                precision: w_precision,
+                scale: w_scale,
+                ..
+            },
+        ) => precision == w_precision && scale == w_scale,
+        (Variant::Uuid { .. }, Variant::Uuid { .. })
+        | (Variant::Duration { .. }, Variant::Duration { .. }) => true,
+        (
+            Variant::Custom { logical, .. },
+            Variant::Custom {
+                logical: w_logical, ..
+            },
+        ) => logical == w_logical,
+        _ => r_schema.temporal_underlying().is_some() && r_schema == w_schema,
+    }
+}
+
+fn mismatch(r_schema: &Variant, w_schema: &Variant) -> AvrowErr {
+    AvrowErr::SchemaResolutionFailed(format!("{:?}", r_schema), format!("{:?}", w_schema))
+}
@@ -4,6 +4,7 @@
 use crate::error::AvrowErr;
 use crate::schema::Variant;
 use crate::value::Value;
+use indexmap::IndexMap;
 use serde_json::Value as JsonValue;
 use std::fmt::{self, Display};
 use std::str::FromStr;
@@ -142,6 +143,21 @@ impl Name {
         Ok(())
     }
 
+    // Returns the fullnames a reader's named schema is known by during schema resolution: its fullname
+    // followed by its aliases. Aliases without a namespace are relative to the namespace of this name.
+    pub(crate) fn with_aliases(&self, aliases: Option<&Vec<String>>) -> Vec<String> {
+        let mut names = vec![self.fullname()];
+        for alias in aliases.into_iter().flatten() {
+            match self.namespace() {
+                Some(namespace) if !alias.contains('.') && !namespace.is_empty() => {
+                    names.push(format!("{}.{}", namespace, alias))
+                }
+                _ => names.push(alias.to_string()),
+            }
+        }
+        names
+    }
+
     // TODO according to Rust convention, item path separators are :: instead of .
     // should we add a configurable separator?
     pub(crate) fn fullname(&self) -> String {
@@ -250,6 +266,21 @@ impl Field {
             aliases,
         })
     }
+
+    // Returns the names a reader's field is known by during schema resolution: its name followed by its aliases.
+    pub(crate) fn names(&self) -> Vec<&str> {
+        let mut names = vec![self.name.as_str()];
+        names.extend(self.aliases.iter().flatten().map(|a| a.as_str()));
+        names
+    }
+
+    // Finds the writer's field this reader's field resolves to, by name or by alias.
+    pub(crate) fn find_in<'a>(
+        &self,
+        writer_fields: &'a IndexMap<String, Field>,
+    ) -> Option<&'a Field> {
+        self.names().into_iter().find_map(|n| writer_fields.get(n))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,489 @@
+//! Contains the schema compatibility checker. It walks a reader and a writer schema
+//! and applies the schema resolution rules from the spec without reading any data.
+
+use super::common::Name;
+use super::{union_branch, Registry, Schema, Variant};
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+/// The compatibility guarantee to check a new schema against previous versions of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompatibilityMode {
+    /// Data written with the previous schema can be read with the new schema.
+    Backward,
+    /// Data written with the new schema can be read with the previous schema.
+    Forward,
+    /// Both backward and forward compatible with the previous schema.
+    Full,
+    /// Backward compatible with all previous schemas.
+    BackwardTransitive,
+    /// Forward compatible with all previous schemas.
+    ForwardTransitive,
+    /// Both backward and forward compatible with all previous schemas.
+    FullTransitive,
+}
+
+impl CompatibilityMode {
+    fn is_transitive(self) -> bool {
+        matches!(
+            self,
+            CompatibilityMode::BackwardTransitive
+                | CompatibilityMode::ForwardTransitive
+                | CompatibilityMode::FullTransitive
+        )
+    }
+
+    fn backward(self) -> bool {
+        !matches!(
+            self,
+            CompatibilityMode::Forward | CompatibilityMode::ForwardTransitive
+        )
+    }
+
+    fn forward(self) -> bool {
+        !matches!(
+            self,
+            CompatibilityMode::Backward | CompatibilityMode::BackwardTransitive
+        )
+    }
+}
+
+/// A single incompatibility between a reader and a writer schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incompatibility {
+    /// Location of the incompatible part, as a path into the schema reading the data, e.g
+    /// `/fields/tags/type/items`. That is the new schema when checking backward compatibility,
+    /// and the previous schema when checking forward compatibility.
+    pub path: String,
+    /// Description of why the reading schema cannot read the writing schema at `path`.
+    pub reason: String,
+    /// Index of the previous schema in the history this incompatibility was found with, when
+    /// checking with [check_compatibility_with_history](struct.Schema.html#method.check_compatibility_with_history).
+    pub history_index: Option<usize>,
+}
+
+impl Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(idx) = self.history_index {
+            write!(f, "schema {} in history: ", idx)?;
+        }
+        write!(f, "{}: {}", self.path, self.reason)
+    }
+}
+
+impl Schema {
+    /// Checks whether `reader` is compatible with `writer` under the given mode, where `reader`
+    /// is the new schema and `writer` the previous one. For backward compatibility `reader` must
+    /// be able to read data written with `writer`, and for forward compatibility the other way around.
+    /// Returns every incompatibility found, which is empty if the schemas are compatible.
+    /// Transitive modes behave like their non-transitive counterparts for a single schema.
+    /// ```rust
+    /// use avrow::{CompatibilityMode, Schema};
+    /// use std::str::FromStr;
+    ///
+    /// let old = Schema::from_str(r##"{"type": "record", "name": "User", "fields": [
+    ///     {"name": "id", "type": "int"}
+    /// ]}"##).unwrap();
+    /// let new = Schema::from_str(r##"{"type": "record", "name": "User", "fields": [
+    ///     {"name": "id", "type": "long"},
+    ///     {"name": "email", "type": "string"}
+    /// ]}"##).unwrap();
+    ///
+    /// let incompatibilities = Schema::check_compatibility(&new, &old, CompatibilityMode::Backward);
+    /// assert_eq!(incompatibilities[0].path, "/fields/email");
+    /// ```
+    pub fn check_compatibility(
+        reader: &Schema,
+        writer: &Schema,
+        mode: CompatibilityMode,
+    ) -> Vec<Incompatibility> {
+        let mut incompatibilities = vec![];
+        if mode.backward() {
+            incompatibilities.extend(can_read(reader, writer));
+        }
+        if mode.forward() {
+            incompatibilities.extend(can_read(writer, reader));
+        }
+        incompatibilities
+    }
+
+    /// Checks whether this schema is compatible with a list of previous schemas, ordered from
+    /// oldest to latest. Non-transitive modes only check against the latest schema.
+    /// Each incompatibility records the index of the schema in `history` it was found with.
+    pub fn check_compatibility_with_history(
+        &self,
+        history: &[Schema],
+        mode: CompatibilityMode,
+    ) -> Vec<Incompatibility> {
+        let skip = if mode.is_transitive() {
+            0
+        } else {
+            history.len().saturating_sub(1)
+        };
+        let mut incompatibilities = vec![];
+        for (idx, previous) in history.iter().enumerate().skip(skip) {
+            for mut i in Schema::check_compatibility(self, previous, mode) {
+                i.history_index = Some(idx);
+                incompatibilities.push(i);
+            }
+        }
+        incompatibilities
+    }
+}
+
+fn can_read(reader: &Schema, writer: &Schema) -> Vec<Incompatibility> {
+    let mut checker = Checker {
+        r_cxt: &reader.cxt,
+        w_cxt: &writer.cxt,
+        seen: HashSet::new(),
+        incompatibilities: vec![],
+    };
+    checker.check(&reader.variant, &writer.variant, "");
+    checker.incompatibilities
+}
+
+struct Checker<'a> {
+    r_cxt: &'a Registry,
+    w_cxt: &'a Registry,
+    // pairs of named reader and writer schemas already checked, to stop at recursive types
+    seen: HashSet<(String, String)>,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, path: &str, reason: String) {
+        let path = if path.is_empty() { "/" } else { path };
+        self.incompatibilities.push(Incompatibility {
+            path: path.to_string(),
+            reason,
+            history_index: None,
+        });
+    }
+
+    fn check(&mut self, r_schema: &'a Variant, w_schema: &'a Variant, path: &str) {
+        let r_schema = r_schema.underlying(self.r_cxt);
+        let w_schema = w_schema.underlying(self.w_cxt);
+        match (w_schema, r_schema) {
+            (Variant::Null, Variant::Null)
+            | (Variant::Boolean, Variant::Boolean)
+            | (Variant::Int, Variant::Int)
+            | (Variant::Int, Variant::Long)
+            | (Variant::Int, Variant::Float)
+            | (Variant::Int, Variant::Double)
+            | (Variant::Long, Variant::Long)
+            | (Variant::Long, Variant::Float)
+            | (Variant::Long, Variant::Double)
+            | (Variant::Float, Variant::Float)
+            | (Variant::Float, Variant::Double)
+            | (Variant::Double, Variant::Double)
+            | (Variant::Bytes, Variant::Bytes)
+            | (Variant::Bytes, Variant::Str)
+            | (Variant::Str, Variant::Str)
+            | (Variant::Str, Variant::Bytes) => {}
+            (Variant::Array { items: w_items }, Variant::Array { items: r_items }) => {
+                self.check(r_items, w_items, &format!("{}/items", path))
+            }
+            (Variant::Map { values: w_values }, Variant::Map { values: r_values }) => {
+                self.check(r_values, w_values, &format!("{}/values", path))
+            }
+            (
+                Variant::Record {
+                    name: w_name,
+                    fields: w_fields,
+                    ..
+                },
+                Variant::Record {
+                    name: r_name,
+                    aliases: r_aliases,
+                    fields: r_fields,
+                },
+            ) => {
+                if !self.names_match(r_name, r_aliases, w_name, path) {
+                    return;
+                }
+                if !self.seen.insert((r_name.fullname(), w_name.fullname())) {
+                    return;
+                }
+                for (fname, r_field) in r_fields {
+                    let field_path = format!("{}/fields/{}", path, fname);
+                    if let Some(w_field) = r_field.find_in(w_fields) {
+                        self.check(&r_field.ty, &w_field.ty, &format!("{}/type", field_path));
+                    } else if r_field.default.is_none() {
+                        self.report(
+                            &field_path,
+                            format!(
+                                "reader field `{}` has no default and is missing in writer record `{}`",
+                                fname,
+                                w_name.fullname()
+                            ),
+                        );
+                    }
+                }
+            }
+            (
+                Variant::Enum {
+                    name: w_name,
+                    symbols: w_symbols,
+                    ..
+                },
+                Variant::Enum {
+                    name: r_name,
+                    aliases: r_aliases,
+                    symbols: r_symbols,
+                    default: r_default,
+                },
+            ) => {
+                if !self.names_match(r_name, r_aliases, w_name, path) {
+                    return;
+                }
+                let missing: Vec<_> = w_symbols
+                    .iter()
+                    .filter(|s| !r_symbols.contains(s))
+                    .collect();
+                if !missing.is_empty() && r_default.is_none() {
+                    self.report(
+                        &format!("{}/symbols", path),
+                        format!(
+                            "reader enum has no default and is missing writer symbols {:?}",
+                            missing
+                        ),
+                    );
+                }
+            }
+            (
+                Variant::Fixed {
+                    name: w_name,
+                    size: w_size,
+                    ..
+                },
+                Variant::Fixed {
+                    name: r_name,
+                    aliases: r_aliases,
+                    size: r_size,
+                },
+            ) => {
+                if !self.names_match(r_name, r_aliases, w_name, path) {
+                    return;
+                }
+                if w_size != r_size {
+                    self.report(
+                        &format!("{}/size", path),
+                        format!(
+                            "reader fixed has size {}, writer fixed has size {}",
+                            r_size, w_size
+                        ),
+                    );
+                }
+            }
+            // Every branch of the writer's union must be readable by the reader.
+            (Variant::Union { variants }, _) => {
+                for (idx, w_variant) in variants.iter().enumerate() {
+                    match r_schema {
+                        Variant::Union {
+                            variants: r_variants,
+                        } if union_branch(r_variants, w_variant, self.r_cxt, self.w_cxt)
+                            .is_none() =>
+                        {
+                            self.report(
+                                path,
+                                format!(
+                                    "writer union branch {} ({}) is not in reader union",
+                                    idx,
+                                    type_name(w_variant)
+                                ),
+                            )
+                        }
+                        _ => self.check(r_schema, w_variant, path),
+                    }
+                }
+            }
+            // The reader's branch that the writer's schema is resolved against must be readable.
+            (_, Variant::Union { variants }) => {
+                match union_branch(variants, w_schema, self.r_cxt, self.w_cxt) {
+                    Some(idx) => self.check(&variants[idx], w_schema, &format!("{}/{}", path, idx)),
+                    None => self.report(
+                        path,
+                        format!("writer type {} is not in reader union", type_name(w_schema)),
+                    ),
+                }
+            }
+            (w, r) => self.report(
+                path,
+                format!(
+                    "reader type {} cannot read writer type {}",
+                    type_name(r),
+                    type_name(w)
+                ),
+            ),
+        }
+    }
+
+    fn names_match(
+        &mut self,
+        r_name: &Name,
+        r_aliases: &Option<Vec<String>>,
+        w_name: &Name,
+        path: &str,
+    ) -> bool {
+        let tried = r_name.with_aliases(r_aliases.as_ref());
+        if tried.contains(&w_name.fullname()) {
+            true
+        } else {
+            self.report(
+                &format!("{}/name", path),
+                format!(
+                    "reader names {:?} do not match writer name `{}`",
+                    tried,
+                    w_name.fullname()
+                ),
+            );
+            false
+        }
+    }
+}
+
+pub(super) fn type_name(schema: &Variant) -> String {
+    match schema {
+        Variant::Null => "null".to_string(),
+        Variant::Boolean => "boolean".to_string(),
+        Variant::Int => "int".to_string(),
+        Variant::Long => "long".to_string(),
+        Variant::Float => "float".to_string(),
+        Variant::Double => "double".to_string(),
+        Variant::Bytes => "bytes".to_string(),
+        Variant::Str => "string".to_string(),
+        Variant::Record { name, .. } => format!("record `{}`", name.fullname()),
+        Variant::Enum { name, .. } => format!("enum `{}`", name.fullname()),
+        Variant::Fixed { name, .. } => format!("fixed `{}`", name.fullname()),
+        Variant::Array { .. } => "array".to_string(),
+        Variant::Map { .. } => "map".to_string(),
+        Variant::Union { .. } => "union".to_string(),
+        Variant::Named(name) => format!("`{}`", name),
+        Variant::Decimal { inner, .. }
+        | Variant::Uuid { inner }
+        | Variant::Duration { inner }
+        | Variant::Custom { inner, .. } => type_name(inner),
+        other => type_name(&other.temporal_underlying().unwrap_or(Variant::Null)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompatibilityMode::*;
+    use crate::schema::Schema;
+    use std::str::FromStr;
+
+    fn schema(s: &str) -> Schema {
+        Schema::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn primitive_promotions() {
+        let int = schema(r##""int""##);
+        let long = schema(r##""long""##);
+        assert!(Schema::check_compatibility(&long, &int, Backward).is_empty());
+        let found = Schema::check_compatibility(&long, &int, Forward);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "/");
+        assert_eq!(Schema::check_compatibility(&long, &int, Full), found);
+    }
+
+    #[test]
+    fn reports_all_incompatibilities_with_paths() {
+        let writer = schema(
+            r##"{"type": "record", "name": "LongList", "fields": [
+                {"name": "value", "type": "long"},
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+                {"name": "next", "type": ["null", "LongList"]}
+            ]}"##,
+        );
+        let reader = schema(
+            r##"{"type": "record", "name": "LongList", "fields": [
+                {"name": "value", "type": "int"},
+                {"name": "tags", "type": {"type": "array", "items": "int"}},
+                {"name": "next", "type": ["null", "LongList"]},
+                {"name": "size", "type": "long"},
+                {"name": "other", "type": "long", "default": 0}
+            ]}"##,
+        );
+        let found = Schema::check_compatibility(&reader, &writer, Backward);
+        let paths: Vec<_> = found.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/fields/value/type",
+                "/fields/tags/type/items",
+                "/fields/size"
+            ]
+        );
+    }
+
+    #[test]
+    fn unions_enums_and_fixed() {
+        let writer = schema(r##"["null", "int", "string"]"##);
+        let reader = schema(r##"["null", "long"]"##);
+        let found = Schema::check_compatibility(&reader, &writer, Backward);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].reason.contains("branch 2"));
+
+        let writer = schema(r##"{"type": "enum", "name": "Suit", "symbols": ["A", "B"]}"##);
+        let reader = schema(r##"{"type": "enum", "name": "Suit", "symbols": ["B", "A", "C"]}"##);
+        assert!(Schema::check_compatibility(&reader, &writer, Backward).is_empty());
+        let found = Schema::check_compatibility(&reader, &writer, Forward);
+        assert_eq!(found[0].path, "/symbols");
+        let writer =
+            schema(r##"{"type": "enum", "name": "Suit", "symbols": ["A", "B"], "default": "A"}"##);
+        assert!(Schema::check_compatibility(&reader, &writer, Full).is_empty());
+
+        let writer = schema(r##"{"type": "fixed", "name": "md5", "size": 16}"##);
+        let reader = schema(r##"{"type": "fixed", "name": "sha", "size": 20}"##);
+        let found = Schema::check_compatibility(&reader, &writer, Full);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].path, "/name");
+    }
+
+    #[test]
+    fn renames_with_aliases() {
+        let writer = schema(
+            r##"{"type": "record", "name": "Person", "fields": [{"name": "id", "type": "int"}]}"##,
+        );
+        let reader = schema(
+            r##"{"type": "record", "name": "User", "aliases": ["Person"], "fields": [
+                {"name": "key", "aliases": ["id"], "type": "long"}
+            ]}"##,
+        );
+        assert!(Schema::check_compatibility(&reader, &writer, Backward).is_empty());
+        let found = Schema::check_compatibility(&reader, &writer, Forward);
+        assert_eq!(found[0].path, "/name");
+    }
+
+    #[test]
+    fn transitive_checks_all_history() {
+        let history = vec![
+            schema(r##"{"type": "record", "name": "User", "fields": []}"##),
+            schema(
+                r##"{"type": "record", "name": "User", "fields": [
+                {"name": "email", "type": "string"}]}"##,
+            ),
+        ];
+        let new = schema(
+            r##"{"type": "record", "name": "User", "fields": [
+            {"name": "email", "type": "string"}]}"##,
+        );
+        assert!(new
+            .check_compatibility_with_history(&history, Backward)
+            .is_empty());
+        let found = new.check_compatibility_with_history(&history, BackwardTransitive);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].history_index, Some(0));
+        assert_eq!(
+            found[0].to_string(),
+            format!("schema 0 in history: /fields/email: {}", found[0].reason)
+        );
+        assert_eq!(
+            new.check_compatibility_with_history(&history, FullTransitive)
+                .len(),
+            1
+        );
+    }
+}
@@ -8,10 +8,15 @@ mod tests;
 use crate::error::AvrowErr;
 pub use common::Order;
 mod canonical;
+mod compat;
+pub use compat::{CompatibilityMode, Incompatibility};
+mod validation;
+pub use validation::Violation;
 pub mod parser;
 pub(crate) use parser::Registry;
 
 use crate::error::AvrowResult;
+use crate::logical::{CustomLogical, LogicalType};
 use crate::value::Value;
 use canonical::normalize_schema;
 use canonical::CanonicalSchema;
@@ -21,6 +26,7 @@ use serde_json::{self, Value as JsonValue};
 use std::fmt::Debug;
 use std::fs::OpenOptions;
 use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum Variant {
@@ -39,12 +45,14 @@ pub(crate) enum Variant {
     },
     Fixed {
         name: Name,
+        aliases: Option<Vec<String>>,
         size: usize,
     },
     Enum {
         name: Name,
         aliases: Option<Vec<String>>,
         symbols: Vec<String>,
+        default: Option<String>,
     },
     Map {
         values: Box<Variant>,
@@ -56,6 +64,29 @@ pub(crate) enum Variant {
         variants: Vec<Variant>,
     },
     Named(String),
+    // Logical types, annotating their underlying avro type.
+    Decimal {
+        precision: usize,
+        scale: usize,
+        inner: Box<Variant>,
+    },
+    Date,
+    TimeMillis,
+    TimeMicros,
+    TimestampMillis,
+    TimestampMicros,
+    LocalTimestampMillis,
+    LocalTimestampMicros,
+    Uuid {
+        inner: Box<Variant>,
+    },
+    Duration {
+        inner: Box<Variant>,
+    },
+    Custom {
+        logical: CustomLogical,
+        inner: Box<Variant>,
+    },
 }
 
 /// Represents the avro schema used to write encoded avro data.
@@ -114,6 +145,28 @@ impl Schema {
         Schema::parse_imp(value)
     }
 
+    /// Registers a custom logical type on this schema. Any part of the schema annotated with the
+    /// name of the logical type gets validated and converted by it. Schemas annotated with logical
+    /// types that are not registered are read and written as their underlying type.
+    pub fn register_logical_type<L: LogicalType + 'static>(
+        &mut self,
+        logical_type: L,
+    ) -> AvrowResult<()> {
+        let mut parser = self.cxt.without_named_types();
+        parser.register_logical_type(CustomLogical(Arc::new(logical_type)));
+        self.variant = parser.parse_schema(&self.inner, None)?;
+        self.cxt = parser;
+        Ok(())
+    }
+
+    // Parses this schema again, also annotating it with the custom logical types registered in
+    // `cxt`. A writer's schema read from a datafile header has no custom logical types registered.
+    pub(crate) fn with_logical_types_of(&self, cxt: &Registry) -> AvrowResult<(Variant, Registry)> {
+        let mut parser = self.cxt.with_logical_types_of(cxt);
+        let variant = parser.parse_schema(&self.inner, None)?;
+        Ok((variant, parser))
+    }
+
     fn parse_imp(schema_json: JsonValue) -> AvrowResult<Self> {
         let mut parser = Registry::new();
         let pcf = CanonicalSchema(normalize_schema(&schema_json)?);
@@ -140,6 +193,13 @@ impl Schema {
         self.variant.validate(value, &self.cxt)
     }
 
+    /// Checks a value against this schema and returns every part of it that does not match,
+    /// which is empty if the value is valid. Every record field, array item and map value is checked.
+    /// Fields missing from a record are valid if the schema has a default for them.
+    pub fn violations(&self, value: &Value) -> Vec<Violation> {
+        validation::violations(&self.variant, value, &self.cxt)
+    }
+
     /// Returns the canonical form of an Avro schema.
     /// Example:
     /// ```rust
@@ -165,94 +225,90 @@ impl Schema {
     }
 }
 
+// Returns the index of the first branch of the reader's union that matches the writer's schema.
+// Branches of the same type are preferred over branches the writer's type can be promoted to.
+pub(crate) fn union_branch(
+    reader_variants: &[Variant],
+    w_schema: &Variant,
+    r_cxt: &Registry,
+    w_cxt: &Registry,
+) -> Option<usize> {
+    let w_schema = w_schema.underlying(w_cxt);
+    let matches = |promote: bool| {
+        reader_variants
+            .iter()
+            .position(|r| r.underlying(r_cxt).matches(w_schema, promote))
+    };
+    matches(false).or_else(|| matches(true))
+}
+
 impl Variant {
     pub fn validate(&self, value: &Value, cxt: &Registry) -> AvrowResult<()> {
-        let variant = self;
-        match (value, variant) {
-            (Value::Null, Variant::Null)
-            | (Value::Boolean(_), Variant::Boolean)
-            | (Value::Int(_), Variant::Int)
-            // long is promotable to float or double
-            | (Value::Long(_), Variant::Long)
-            | (Value::Long(_), Variant::Float)
-            | (Value::Long(_), Variant::Double)
-            // int is promotable to long, float or double
-            | (Value::Int(_), Variant::Long)
-            | (Value::Int(_), Variant::Float)
-            | (Value::Int(_), Variant::Double)
-            | (Value::Float(_), Variant::Float)
-            // float is promotable to double
-            | (Value::Float(_), Variant::Double)
-            | (Value::Double(_), Variant::Double)
-            | (Value::Str(_), Variant::Str)
-            // string is promotable to bytes
-            | (Value::Str(_), Variant::Bytes)
-            // bytes is promotable to string
-            | (Value::Bytes(_), Variant::Str)
-            | (Value::Bytes(_), Variant::Bytes) => {},
-            (Value::Fixed(v), Variant::Fixed { size, .. })
-            | (Value::Bytes(v), Variant::Fixed { size, .. }) => {
-                if v.len() != *size {
-                    return Err(AvrowErr::FixedValueLenMismatch {
-                        found: v.len(),
-                        expected: *size,
-                    });
-                }
-            }
-            (Value::Record(rec), Variant::Record { ref fields, .. }) => {
-                for (fname, fvalue) in &rec.fields {
-                    if let Some(ftype) = fields.get(fname) {
-                        ftype.ty.validate(&fvalue.value, cxt)?;
-                    } else {
-                        return Err(AvrowErr::RecordFieldMissing);
-                    }
-                }
-            }
-            (Value::Map(hmap), Variant::Map { values }) => {
-                return if let Some(v) = hmap.values().next() {
-                    values.validate(v, cxt)
-                } else {
-                    Err(AvrowErr::EmptyMap)
-                }
-            }
-            (Value::Enum(sym), Variant::Enum { symbols, .. }) if symbols.contains(sym) => {
-                return Ok(())
-            }
-            (Value::Array(item), Variant::Array { items }) => {
-                return if let Some(v) = item.first() {
-                    items.validate(v, cxt)
-                } else {
-                    Err(AvrowErr::EmptyArray)
-                }
-            }
-            (v, Variant::Named(name)) => {
-                if let Some(schema) = cxt.get(&name) {
-                    if schema.validate(v, cxt).is_ok() {
-                        return Ok(());
-                    }
-                }
-                return Err(AvrowErr::NamedSchemaNotFoundForValue)
-            }
-            // Value `a` can be any of the above schemas + any named schema in the schema registry
-            (a, Variant::Union { variants }) => {
-                for s in variants.iter() {
-                    if s.validate(a, cxt).is_ok() {
-                        return Ok(());
-                    }
-                }
+        let violations = validation::violations(self, value, cxt);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AvrowErr::ValidationFailed(violations))
+        }
+    }
 
-                return Err(AvrowErr::NotFoundInUnion)
-            }
+    // Returns the underlying avro type if this is a temporal logical type.
+    pub(crate) fn temporal_underlying(&self) -> Option<Variant> {
+        match self {
+            Variant::Date | Variant::TimeMillis => Some(Variant::Int),
+            Variant::TimeMicros
+            | Variant::TimestampMillis
+            | Variant::TimestampMicros
+            | Variant::LocalTimestampMillis
+            | Variant::LocalTimestampMicros => Some(Variant::Long),
+            _ => None,
+        }
+    }
 
-            (v, s) => {
-                return Err(AvrowErr::SchemaDataValidationFailed(
-                    format!("{:?}", v),
-                    format!("{:?}", s),
-                ))
-            }
+    // Resolves a named reference in the given context, and returns the underlying type of logical types,
+    // which are resolved using their underlying types.
+    pub(crate) fn underlying<'a>(&'a self, cxt: &'a Registry) -> &'a Variant {
+        match self {
+            Variant::Named(name) => match cxt.get(name) {
+                Some(named) => named.underlying(cxt),
+                None => self,
+            },
+            Variant::Decimal { inner, .. }
+            | Variant::Uuid { inner }
+            | Variant::Duration { inner }
+            | Variant::Custom { inner, .. } => inner.underlying(cxt),
+            Variant::Date | Variant::TimeMillis => &Variant::Int,
+            Variant::TimeMicros
+            | Variant::TimestampMillis
+            | Variant::TimestampMicros
+            | Variant::LocalTimestampMillis
+            | Variant::LocalTimestampMicros => &Variant::Long,
+            _ => self,
         }
+    }
 
-        Ok(())
+    // Returns true if this reader's schema matches the writer's schema when picking a branch of the
+    // reader's union. Only names and kinds are compared, not the items, values or fields of the schemas.
+    // Both schemas are expected to be resolved to their underlying types.
+    pub(crate) fn matches(&self, w_schema: &Variant, promote: bool) -> bool {
+        match (w_schema, self) {
+            (Variant::Record { name: w_name, .. }, Variant::Record { name, aliases, .. })
+            | (Variant::Enum { name: w_name, .. }, Variant::Enum { name, aliases, .. })
+            | (Variant::Fixed { name: w_name, .. }, Variant::Fixed { name, aliases, .. }) => name
+                .with_aliases(aliases.as_ref())
+                .contains(&w_name.fullname()),
+            (Variant::Array { .. }, Variant::Array { .. })
+            | (Variant::Map { .. }, Variant::Map { .. }) => true,
+            (Variant::Int, Variant::Long)
+            | (Variant::Int, Variant::Float)
+            | (Variant::Int, Variant::Double)
+            | (Variant::Long, Variant::Float)
+            | (Variant::Long, Variant::Double)
+            | (Variant::Float, Variant::Double)
+            | (Variant::Str, Variant::Bytes)
+            | (Variant::Bytes, Variant::Str) => promote,
+            (w, r) => w == r,
+        }
     }
 
     fn get_named_mut(&mut self) -> Option<&mut Name> {
@@ -260,6 +316,10 @@ impl Variant {
             Variant::Record { name, .. }
             | Variant::Fixed { name, .. }
             | Variant::Enum { name, .. } => Some(name),
+            Variant::Decimal { inner, .. }
+            | Variant::Uuid { inner }
+            | Variant::Duration { inner }
+            | Variant::Custom { inner, .. } => inner.get_named_mut(),
             _ => None,
         }
     }
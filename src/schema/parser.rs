@@ -3,6 +3,10 @@ use super::Variant;
 use crate::error::io_err;
 use crate::error::AvrowErr;
 use crate::error::AvrowResult;
+use crate::logical::{
+    duration_from_raw, max_fixed_precision, temporal_from_raw, uuid_from_raw, CustomLogical,
+    Decimal,
+};
 use crate::schema::common::validate_name;
 use crate::value::FieldValue;
 use crate::value::Value;
@@ -18,15 +22,51 @@ use std::collections::HashMap;
 pub(crate) struct Registry {
     // TODO: use a reference to Variant?
     cxt: HashMap<String, Variant>,
+    // Custom logical types registered by the user, keyed by their name
+    logical_types: HashMap<String, CustomLogical>,
+    // Whether `error` types are accepted, which are only allowed in protocols
+    error_types: bool,
 }
 
 impl Registry {
     pub(crate) fn new() -> Self {
         Self {
             cxt: HashMap::new(),
+            logical_types: HashMap::new(),
+            error_types: false,
         }
     }
 
+    // Returns a registry for parsing the types and messages of a protocol.
+    pub(crate) fn for_protocol() -> Self {
+        Self {
+            error_types: true,
+            ..Self::new()
+        }
+    }
+
+    // Returns a registry with the same custom logical types, to re-parse a schema with.
+    pub(crate) fn without_named_types(&self) -> Self {
+        Self {
+            cxt: HashMap::new(),
+            logical_types: self.logical_types.clone(),
+            error_types: self.error_types,
+        }
+    }
+
+    // Returns a registry with the custom logical types of both registries, preferring this one's.
+    pub(crate) fn with_logical_types_of(&self, other: &Registry) -> Self {
+        let mut registry = other.without_named_types();
+        registry.logical_types.extend(self.logical_types.clone());
+        registry.error_types = self.error_types;
+        registry
+    }
+
+    pub(crate) fn register_logical_type(&mut self, logical: CustomLogical) {
+        self.logical_types
+            .insert(logical.0.name().to_string(), logical);
+    }
+
     pub(crate) fn get<'a>(&'a self, name: &str) -> Option<&'a Variant> {
         self.cxt.get(name)
     }
@@ -38,7 +78,10 @@ impl Registry {
     ) -> Result<Variant, AvrowErr> {
         match value {
             // Parse a complex schema
-            JsonValue::Object(ref schema) => self.parse_object(schema, enclosing_namespace),
+            JsonValue::Object(ref schema) => {
+                let variant = self.parse_object(schema, enclosing_namespace)?;
+                self.parse_custom_logical_type(schema, variant)
+            }
             // Parse a primitive schema, could also be a named schema reference
             JsonValue::String(ref schema) => self.parse_primitive(&schema, enclosing_namespace),
             // Parse a union schema
@@ -73,6 +116,38 @@ impl Registry {
         })
     }
 
+    // Annotates a schema with a registered custom logical type, if any.
+    fn parse_custom_logical_type(
+        &mut self,
+        value: &Map<String, JsonValue>,
+        underlying: Variant,
+    ) -> Result<Variant, AvrowErr> {
+        let logical = match value.get("logicalType") {
+            Some(JsonValue::String(l)) => match self.logical_types.get(l) {
+                Some(logical) => logical.clone(),
+                None => return Ok(underlying),
+            },
+            _ => return Ok(underlying),
+        };
+        logical.0.validate_schema(value)?;
+
+        let name = match underlying {
+            Variant::Record { ref name, .. }
+            | Variant::Fixed { ref name, .. }
+            | Variant::Enum { ref name, .. } => Some(name.fullname()),
+            _ => None,
+        };
+        let variant = Variant::Custom {
+            logical,
+            inner: Box::new(underlying),
+        };
+        // references to a named schema must also resolve to the logical type
+        if let Some(name) = name {
+            self.cxt.insert(name, variant.clone());
+        }
+        Ok(variant)
+    }
+
     fn get_fullname(&self, name: &str, enclosing_namespace: Option<&str>) -> String {
         if let Some(namespace) = enclosing_namespace {
             format!("{}.{}", namespace, name)
@@ -111,7 +186,7 @@ impl Registry {
         }
     }
 
-    fn parse_record_fields(
+    pub(crate) fn parse_record_fields(
         &mut self,
         fields: &[serde_json::Value],
         enclosing_namespace: Option<&str>,
@@ -174,7 +249,10 @@ impl Registry {
         enclosing_namespace: Option<&str>,
     ) -> Result<Variant, AvrowErr> {
         match value.get("type") {
-            Some(&JsonValue::String(ref s)) if s == "record" => {
+            // errors declared in a protocol are parsed as records
+            Some(&JsonValue::String(ref s))
+                if s == "record" || (s == "error" && self.error_types) =>
+            {
                 let rec_name = Name::from_json(value, enclosing_namespace)?;
 
                 // Insert a named reference to support recursive schema definitions.
@@ -239,12 +317,21 @@ impl Registry {
                     return Err(AvrowErr::EnumSymbolsMissing);
                 }
 
+                // The default symbol is used during schema resolution when the writer's symbol is not
+                // present in the reader's enum.
+                let default = match value.get("default") {
+                    Some(JsonValue::String(d)) if symbols.contains(d) => Some(d.to_string()),
+                    Some(other) => return Err(AvrowErr::InvalidEnumDefault(other.to_string())),
+                    None => None,
+                };
+
                 let name_str = name.fullname();
 
                 let enum_schema = Variant::Enum {
                     name,
                     aliases,
                     symbols,
+                    default,
                 };
 
                 self.cxt.insert(name_str, enum_schema.clone());
@@ -274,11 +361,14 @@ impl Registry {
                 let name = Name::from_json(value, enclosing_namespace)?;
                 let size = value.get("size").ok_or(AvrowErr::FixedSizeNotFound)?;
                 let name_str = name.fullname();
+                let aliases = parse_aliases(value.get("aliases"));
 
                 let fixed_schema = Variant::Fixed {
                     name,
+                    aliases,
                     size: size.as_u64().ok_or(AvrowErr::FixedSizeNotNumber)? as usize, // clamp to usize
                 };
+                let fixed_schema = parse_logical_type(value, fixed_schema)?;
 
                 self.cxt.insert(name_str, fixed_schema.clone());
 
@@ -286,12 +376,18 @@ impl Registry {
             }
             Some(JsonValue::String(ref s)) if s == "null" => Ok(Variant::Null),
             Some(JsonValue::String(ref s)) if s == "boolean" => Ok(Variant::Boolean),
-            Some(JsonValue::String(ref s)) if s == "int" => Ok(Variant::Int),
-            Some(JsonValue::String(ref s)) if s == "long" => Ok(Variant::Long),
+            Some(JsonValue::String(ref s)) if s == "int" => parse_logical_type(value, Variant::Int),
+            Some(JsonValue::String(ref s)) if s == "long" => {
+                parse_logical_type(value, Variant::Long)
+            }
             Some(JsonValue::String(ref s)) if s == "float" => Ok(Variant::Float),
             Some(JsonValue::String(ref s)) if s == "double" => Ok(Variant::Double),
-            Some(JsonValue::String(ref s)) if s == "bytes" => Ok(Variant::Bytes),
-            Some(JsonValue::String(ref s)) if s == "string" => Ok(Variant::Str),
+            Some(JsonValue::String(ref s)) if s == "bytes" => {
+                parse_logical_type(value, Variant::Bytes)
+            }
+            Some(JsonValue::String(ref s)) if s == "string" => {
+                parse_logical_type(value, Variant::Str)
+            }
             _other => Err(AvrowErr::SchemaParseFailed),
         }
     }
@@ -306,6 +402,89 @@ impl Registry {
 //     }
 // }
 
+// Parses the `logicalType` attribute of a schema, annotating the underlying type if it's a
+// known logical type. As per spec, unknown logical types are ignored and the underlying type is used.
+fn parse_logical_type(value: &Map<String, JsonValue>, underlying: Variant) -> AvrowResult<Variant> {
+    match (value.get("logicalType"), &underlying) {
+        (Some(JsonValue::String(ref l)), Variant::Bytes)
+        | (Some(JsonValue::String(ref l)), Variant::Fixed { .. })
+            if l == "decimal" =>
+        {
+            parse_decimal(value, underlying)
+        }
+        (Some(JsonValue::String(ref l)), Variant::Str)
+        | (Some(JsonValue::String(ref l)), Variant::Fixed { size: 16, .. })
+            if l == "uuid" =>
+        {
+            Ok(Variant::Uuid {
+                inner: Box::new(underlying),
+            })
+        }
+        (Some(JsonValue::String(ref l)), Variant::Fixed { size: 12, .. }) if l == "duration" => {
+            Ok(Variant::Duration {
+                inner: Box::new(underlying),
+            })
+        }
+        (Some(JsonValue::String(ref l)), Variant::Int) => match l.as_ref() {
+            "date" => Ok(Variant::Date),
+            "time-millis" => Ok(Variant::TimeMillis),
+            _ => Ok(underlying),
+        },
+        (Some(JsonValue::String(ref l)), Variant::Long) => match l.as_ref() {
+            "time-micros" => Ok(Variant::TimeMicros),
+            "timestamp-millis" => Ok(Variant::TimestampMillis),
+            "timestamp-micros" => Ok(Variant::TimestampMicros),
+            "local-timestamp-millis" => Ok(Variant::LocalTimestampMillis),
+            "local-timestamp-micros" => Ok(Variant::LocalTimestampMicros),
+            _ => Ok(underlying),
+        },
+        _ => Ok(underlying),
+    }
+}
+
+// Parses and validates the `precision` and `scale` attributes of a decimal logical type.
+fn parse_decimal(value: &Map<String, JsonValue>, underlying: Variant) -> AvrowResult<Variant> {
+    let precision = value
+        .get("precision")
+        .and_then(|p| p.as_u64())
+        .ok_or_else(|| {
+            AvrowErr::InvalidDecimalSchema("`precision` must be a positive integer".to_string())
+        })? as usize;
+    let scale = match value.get("scale") {
+        Some(scale) => scale.as_u64().ok_or_else(|| {
+            AvrowErr::InvalidDecimalSchema("`scale` must be a non-negative integer".to_string())
+        })? as usize,
+        None => 0,
+    };
+
+    if precision == 0 {
+        return Err(AvrowErr::InvalidDecimalSchema(
+            "`precision` must be greater than zero".to_string(),
+        ));
+    }
+    if scale > precision {
+        return Err(AvrowErr::InvalidDecimalSchema(format!(
+            "`scale` {} is greater than `precision` {}",
+            scale, precision
+        )));
+    }
+    if let Variant::Fixed { size, .. } = underlying {
+        let max_precision = max_fixed_precision(size);
+        if precision > max_precision {
+            return Err(AvrowErr::InvalidDecimalSchema(format!(
+                "fixed of size {} can hold a precision of at most {}, found {}",
+                size, max_precision, precision
+            )));
+        }
+    }
+
+    Ok(Variant::Decimal {
+        precision,
+        scale,
+        inner: Box::new(underlying),
+    })
+}
+
 // Parses the `order` of a field, defaults to `ascending` order
 pub(crate) fn parse_field_order(order: &JsonValue) -> AvrowResult<Order> {
     match order {
@@ -397,7 +576,20 @@ pub(crate) fn parse_default(
         }
 
         (JsonValue::String(n), Variant::Fixed { .. }) => Ok(Value::Fixed(n.as_bytes().to_vec())),
-        (_d, _s) => Err(AvrowErr::DefaultValueParse),
+        (d, Variant::Decimal { scale, inner, .. }) => match parse_default(d, inner)? {
+            Value::Bytes(b) | Value::Fixed(b) => {
+                Ok(Value::Decimal(Decimal::from_be_bytes(&b, *scale)))
+            }
+            _ => Err(AvrowErr::DefaultValueParse),
+        },
+        (d, Variant::Custom { logical, inner }) => logical.0.to_logical(parse_default(d, inner)?),
+        (d, Variant::Uuid { inner }) => uuid_from_raw(parse_default(d, inner)?),
+        (d, Variant::Duration { inner }) => duration_from_raw(parse_default(d, inner)?),
+        // temporal types take the default of their underlying type
+        (d, s) => match s.temporal_underlying() {
+            Some(underlying) => temporal_from_raw(s, parse_default(d, &underlying)?),
+            None => Err(AvrowErr::DefaultValueParse),
+        },
     }
 }
 
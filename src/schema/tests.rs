@@ -99,6 +99,12 @@ fn parse_record() {
     );
 }
 
+#[test]
+fn error_type_outside_protocol_fails() {
+    let schema = r##"{"type": "error", "name": "Failure", "fields": [{"name": "reason", "type": "string"}]}"##;
+    assert!(Schema::from_str(schema).is_err());
+}
+
 #[test]
 fn parse_fixed() {
     let fixed_schema =
@@ -107,6 +113,7 @@ fn parse_fixed() {
         fixed_schema.variant,
         Variant::Fixed {
             name: Name::new("md5").unwrap(),
+            aliases: None,
             size: 16
         }
     );
@@ -132,11 +139,32 @@ fn parse_enum() {
         Variant::Enum {
             name,
             aliases: None,
-            symbols
+            symbols,
+            default: None,
         }
     );
 }
 
+#[test]
+fn parse_enum_default() {
+    let schema = Schema::from_str(
+        r##"{"type": "enum", "name": "Suit", "symbols": ["SPADES", "HEARTS"], "default": "HEARTS"}"##,
+    )
+    .unwrap();
+    assert!(matches!(
+        schema.variant,
+        Variant::Enum { default: Some(ref d), .. } if d == "HEARTS"
+    ));
+
+    for default in &[r##""CLUBS""##, "1"] {
+        let json = format!(
+            r##"{{"type": "enum", "name": "Suit", "symbols": ["SPADES"], "default": {}}}"##,
+            default
+        );
+        assert!(Schema::from_str(&json).is_err());
+    }
+}
+
 #[test]
 fn parse_array() {
     let json = r##"{"type": "array", "items": "string"}"##;
@@ -453,3 +481,160 @@ fn duplicate_field_name_in_record_fails() {
 
     Schema::from_str(raw_schema).unwrap();
 }
+
+///////////////////////////////////////////////////////////////////////////////
+/// Logical types
+///////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn parse_decimal_on_bytes() {
+    let schema = Schema::from_str(
+        r##"{"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}"##,
+    )
+    .unwrap();
+    assert_eq!(
+        schema.variant,
+        Variant::Decimal {
+            precision: 10,
+            scale: 2,
+            inner: Box::new(Variant::Bytes)
+        }
+    );
+}
+
+#[test]
+fn parse_decimal_on_fixed() {
+    let schema = Schema::from_str(
+        r##"{"type": "fixed", "name": "money", "size": 8, "logicalType": "decimal", "precision": 18}"##,
+    )
+    .unwrap();
+    let decimal = Variant::Decimal {
+        precision: 18,
+        scale: 0,
+        inner: Box::new(Variant::Fixed {
+            name: Name::new("money").unwrap(),
+            aliases: None,
+            size: 8,
+        }),
+    };
+    assert_eq!(schema.variant, decimal);
+    assert_eq!(schema.cxt.get("money"), Some(&decimal));
+}
+
+#[test]
+#[should_panic(expected = "InvalidDecimalSchema")]
+fn decimal_scale_greater_than_precision_fails() {
+    Schema::from_str(
+        r##"{"type": "bytes", "logicalType": "decimal", "precision": 2, "scale": 3}"##,
+    )
+    .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "InvalidDecimalSchema")]
+fn decimal_precision_exceeding_fixed_size_fails() {
+    Schema::from_str(
+        r##"{"type": "fixed", "name": "small", "size": 2, "logicalType": "decimal", "precision": 5}"##,
+    )
+    .unwrap();
+}
+
+#[test]
+fn unknown_logical_type_uses_underlying_type() {
+    let schema = Schema::from_str(r##"{"type": "bytes", "logicalType": "custom-bytes"}"##).unwrap();
+    assert_eq!(schema.variant, Variant::Bytes);
+}
+
+#[test]
+fn parse_temporal_logical_types() {
+    let mut s = HashMap::new();
+    s.insert(r##"{"type": "int", "logicalType": "date"}"##, Variant::Date);
+    s.insert(
+        r##"{"type": "int", "logicalType": "time-millis"}"##,
+        Variant::TimeMillis,
+    );
+    s.insert(
+        r##"{"type": "long", "logicalType": "time-micros"}"##,
+        Variant::TimeMicros,
+    );
+    s.insert(
+        r##"{"type": "long", "logicalType": "timestamp-millis"}"##,
+        Variant::TimestampMillis,
+    );
+    s.insert(
+        r##"{"type": "long", "logicalType": "timestamp-micros"}"##,
+        Variant::TimestampMicros,
+    );
+    s.insert(
+        r##"{"type": "long", "logicalType": "local-timestamp-millis"}"##,
+        Variant::LocalTimestampMillis,
+    );
+    s.insert(
+        r##"{"type": "long", "logicalType": "local-timestamp-micros"}"##,
+        Variant::LocalTimestampMicros,
+    );
+    // logical types annotating the wrong underlying type are ignored
+    s.insert(
+        r##"{"type": "long", "logicalType": "date"}"##,
+        Variant::Long,
+    );
+    s.insert(
+        r##"{"type": "int", "logicalType": "timestamp-millis"}"##,
+        Variant::Int,
+    );
+
+    for (schema, variant) in s {
+        let schema = Schema::from_str(schema).unwrap();
+        assert_eq!(schema.variant, variant);
+    }
+}
+
+#[test]
+fn temporal_field_default() {
+    let schema = Schema::from_str(
+        r##"{"type": "record", "name": "Event", "fields": [
+            {"name": "at", "type": {"type": "long", "logicalType": "timestamp-millis"}, "default": 1000}
+        ]}"##,
+    )
+    .unwrap();
+    if let Variant::Record { fields, .. } = &schema.variant {
+        assert_eq!(
+            fields["at"].default,
+            Some(crate::Value::TimestampMillis(1000))
+        );
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn parse_uuid_and_duration() {
+    let schema = Schema::from_str(r##"{"type": "string", "logicalType": "uuid"}"##).unwrap();
+    assert_eq!(
+        schema.variant,
+        Variant::Uuid {
+            inner: Box::new(Variant::Str)
+        }
+    );
+
+    let schema =
+        Schema::from_str(r##"{"type": "fixed", "name": "id", "size": 16, "logicalType": "uuid"}"##)
+            .unwrap();
+    assert!(matches!(schema.variant, Variant::Uuid { .. }));
+
+    let schema = Schema::from_str(
+        r##"{"type": "fixed", "name": "span", "size": 12, "logicalType": "duration"}"##,
+    )
+    .unwrap();
+    assert!(matches!(
+        schema.cxt.get("span"),
+        Some(Variant::Duration { .. })
+    ));
+
+    // wrongly sized fixed schemas fall back to the underlying fixed
+    let schema = Schema::from_str(
+        r##"{"type": "fixed", "name": "span", "size": 8, "logicalType": "duration"}"##,
+    )
+    .unwrap();
+    assert!(matches!(schema.variant, Variant::Fixed { size: 8, .. }));
+}
@@ -0,0 +1,363 @@
+//! Contains the validator that checks a value against a schema before it gets written.
+//! It visits every field, array item and map value and collects all violations found.
+
+use super::compat::type_name;
+use super::{Registry, Variant};
+use crate::logical::{parse_uuid, Decimal};
+use crate::value::Value;
+use std::fmt::{self, Display};
+
+/// A part of a value that does not match the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Location of the invalid part, as a path of field names, array indices and map keys, e.g `/orders/3/price`.
+    pub path: String,
+    /// The type expected by the schema at `path`.
+    pub expected: String,
+    /// What was found in the value at `path`.
+    pub found: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.path, self.expected, self.found
+        )
+    }
+}
+
+// Returns every violation of `schema` in `value`, which is empty if the value is valid.
+pub(crate) fn violations(schema: &Variant, value: &Value, cxt: &Registry) -> Vec<Violation> {
+    let mut validator = Validator {
+        cxt,
+        violations: vec![],
+    };
+    validator.visit(value, schema, "");
+    validator.violations
+}
+
+struct Validator<'a> {
+    cxt: &'a Registry,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, path: &str, expected: String, found: String) {
+        let path = if path.is_empty() { "/" } else { path };
+        self.violations.push(Violation {
+            path: path.to_string(),
+            expected,
+            found,
+        });
+    }
+
+    fn mismatch(&mut self, path: &str, schema: &Variant, value: &Value) {
+        self.report(path, type_name(schema), value_type(value));
+    }
+
+    fn visit(&mut self, value: &Value, schema: &Variant, path: &str) {
+        match (value, schema) {
+            (Value::Null, Variant::Null)
+            | (Value::Boolean(_), Variant::Boolean)
+            | (Value::Int(_), Variant::Int)
+            // long is promotable to float or double
+            | (Value::Long(_), Variant::Long)
+            | (Value::Long(_), Variant::Float)
+            | (Value::Long(_), Variant::Double)
+            // int is promotable to long, float or double
+            | (Value::Int(_), Variant::Long)
+            | (Value::Int(_), Variant::Float)
+            | (Value::Int(_), Variant::Double)
+            | (Value::Float(_), Variant::Float)
+            // float is promotable to double
+            | (Value::Float(_), Variant::Double)
+            | (Value::Double(_), Variant::Double)
+            | (Value::Str(_), Variant::Str)
+            // string is promotable to bytes
+            | (Value::Str(_), Variant::Bytes)
+            // bytes is promotable to string
+            | (Value::Bytes(_), Variant::Str)
+            | (Value::Bytes(_), Variant::Bytes) => {}
+            (Value::Fixed(v), Variant::Fixed { size, .. })
+            | (Value::Bytes(v), Variant::Fixed { size, .. }) => {
+                if v.len() != *size {
+                    self.report(
+                        path,
+                        type_name(schema),
+                        format!("{} bytes", v.len()),
+                    );
+                }
+            }
+            (
+                Value::Decimal(d),
+                Variant::Decimal {
+                    precision,
+                    scale,
+                    inner,
+                },
+            ) => {
+                let size = match **inner {
+                    Variant::Fixed { size, .. } => Some(size),
+                    _ => None,
+                };
+                let checked = d
+                    .to_schema_bytes(*precision, *scale)
+                    .and_then(|bytes| match size {
+                        Some(size) => crate::logical::sign_extend(&bytes, size).map(|_| ()),
+                        None => Ok(()),
+                    });
+                if let Err(e) = checked {
+                    self.report(path, decimal_name(*precision, *scale), e.to_string());
+                }
+            }
+            (Value::Str(s), Variant::Decimal { .. }) => match s.parse::<Decimal>() {
+                Ok(decimal) => self.visit(&Value::Decimal(decimal), schema, path),
+                Err(e) => self.report(path, type_name(schema), e.to_string()),
+            },
+            // the underlying bytes of a decimal can also be written as is
+            (v, Variant::Decimal { inner, .. }) => self.visit(v, inner, path),
+            (Value::Uuid(s), Variant::Uuid { .. }) | (Value::Str(s), Variant::Uuid { .. }) => {
+                if let Err(e) = parse_uuid(s) {
+                    self.report(path, "uuid".to_string(), e.to_string());
+                }
+            }
+            // the underlying fixed of a uuid can also be written as is
+            (v, Variant::Uuid { inner }) => self.visit(v, inner, path),
+            (Value::Duration { .. }, Variant::Duration { .. }) => {}
+            (v, Variant::Duration { inner }) => self.visit(v, inner, path),
+            (v, Variant::Custom { logical, inner }) => match logical.0.to_underlying(v) {
+                Ok(underlying) => self.visit(&underlying, inner, path),
+                Err(e) => self.report(path, logical.0.name().to_string(), e.to_string()),
+            },
+            (Value::Date(_), Variant::Date)
+            | (Value::TimeMillis(_), Variant::TimeMillis)
+            | (Value::TimeMicros(_), Variant::TimeMicros)
+            | (Value::TimestampMillis(_), Variant::TimestampMillis)
+            | (Value::TimestampMicros(_), Variant::TimestampMicros)
+            | (Value::LocalTimestampMillis(_), Variant::LocalTimestampMillis)
+            | (Value::LocalTimestampMicros(_), Variant::LocalTimestampMicros) => {}
+            #[cfg(feature = "chrono")]
+            (Value::Str(s), v) if v.temporal_underlying().is_some() => {
+                match crate::logical::parse_temporal(s, v) {
+                    Ok(temporal) => self.visit(&temporal, v, path),
+                    Err(e) => self.report(path, type_name(v), e.to_string()),
+                }
+            }
+            (v, s) if s.temporal_underlying().is_some() => {
+                match crate::logical::convert_temporal(v, s) {
+                    Some(Ok(temporal)) => self.visit(&temporal, s, path),
+                    Some(Err(e)) => self.report(path, type_name(s), e.to_string()),
+                    // temporal types can also be written as their underlying int or long
+                    None => {
+                        let underlying = s.temporal_underlying().unwrap();
+                        self.visit(v, &underlying, path)
+                    }
+                }
+            }
+            // Fields that are not in the schema are checked when encoding the record.
+            (Value::Record(rec), Variant::Record { fields, .. }) => {
+                for (fname, field) in fields {
+                    let field_path = format!("{}/{}", path, fname);
+                    if let Some(fvalue) = rec.fields.get(fname) {
+                        self.visit(&fvalue.value, &field.ty, &field_path);
+                    } else if field.default.is_none() {
+                        self.report(
+                            &field_path,
+                            type_name(&field.ty),
+                            "no value and no default".to_string(),
+                        );
+                    }
+                }
+            }
+            (Value::Map(hmap), Variant::Map { values }) => {
+                for (k, v) in hmap {
+                    self.visit(v, values, &format!("{}/{}", path, k));
+                }
+            }
+            (Value::Enum(sym), Variant::Enum { symbols, .. }) => {
+                if !symbols.contains(sym) {
+                    self.report(path, type_name(schema), format!("symbol `{}`", sym));
+                }
+            }
+            (Value::Array(items), Variant::Array { items: items_schema }) => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.visit(item, items_schema, &format!("{}/{}", path, idx));
+                }
+            }
+            (v, Variant::Named(name)) => match self.cxt.get(name) {
+                Some(named) => self.visit(v, named, path),
+                None => self.report(path, type_name(schema), "an undefined schema".to_string()),
+            },
+            (Value::Union { branch, value }, Variant::Union { variants }) => {
+                match (branch, value.as_ref()) {
+                    // unions may not immediately contain other unions
+                    (_, Value::Union { .. }) => {
+                        self.report(path, union_name(variants), value_type(value))
+                    }
+                    (Some(branch), value) => {
+                        let branches: Vec<&Variant> = variants
+                            .iter()
+                            .map(|variant| match variant {
+                                Variant::Named(name) => self.cxt.get(name).unwrap_or(variant),
+                                _ => variant,
+                            })
+                            .collect();
+                        match branch.position(&branches) {
+                            Some(idx) => self.visit(value, branches[idx], path),
+                            None => self.report(path, union_name(variants), value_type(value)),
+                        }
+                    }
+                    (None, value) => self.visit(value, schema, path),
+                }
+            }
+            // Value `v` can be any of the above schemas + any named schema in the schema registry
+            (v, Variant::Union { variants }) => {
+                let mut branch_violations = vec![];
+                for variant in variants {
+                    let mut branch = Validator {
+                        cxt: self.cxt,
+                        violations: vec![],
+                    };
+                    branch.visit(v, variant, path);
+                    if branch.violations.is_empty() {
+                        return;
+                    }
+                    branch_violations.push(branch.violations);
+                }
+                // If the value only failed within one of the branches, e.g in a field of a record,
+                // that branch was meant to be written and its violations are more helpful.
+                let nested: Vec<_> = branch_violations
+                    .into_iter()
+                    .filter(|b| b.iter().all(|i| i.path.len() > path.len().max(1)))
+                    .collect();
+                if nested.len() == 1 {
+                    self.violations.extend(nested.into_iter().flatten());
+                } else {
+                    self.report(path, union_name(variants), value_type(v));
+                }
+            }
+            (v, s) => self.mismatch(path, s, v),
+        }
+    }
+}
+
+fn union_name(variants: &[Variant]) -> String {
+    let names: Vec<_> = variants.iter().map(type_name).collect();
+    format!("one of [{}]", names.join(", "))
+}
+
+fn decimal_name(precision: usize, scale: usize) -> String {
+    format!("decimal({}, {})", precision, scale)
+}
+
+fn value_type(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Boolean(_) => "boolean".to_string(),
+        Value::Int(_) => "int".to_string(),
+        Value::Long(_) => "long".to_string(),
+        Value::Float(_) => "float".to_string(),
+        Value::Double(_) => "double".to_string(),
+        Value::Bytes(_) => "bytes".to_string(),
+        Value::Byte(_) => "byte".to_string(),
+        Value::Str(_) => "string".to_string(),
+        Value::Record(rec) => format!("record `{}`", rec.name),
+        Value::Enum(sym) => format!("enum symbol `{}`", sym),
+        Value::Fixed(v) => format!("fixed of {} bytes", v.len()),
+        Value::Array(_) => "array".to_string(),
+        Value::Map(_) => "map".to_string(),
+        Value::Union { branch: None, .. } => "union".to_string(),
+        Value::Union {
+            branch: Some(branch),
+            ..
+        } => format!("union branch {}", branch),
+        Value::Decimal(_) => "decimal".to_string(),
+        Value::Date(_) => "date".to_string(),
+        Value::TimeMillis(_) => "time-millis".to_string(),
+        Value::TimeMicros(_) => "time-micros".to_string(),
+        Value::TimestampMillis(_) => "timestamp-millis".to_string(),
+        Value::TimestampMicros(_) => "timestamp-micros".to_string(),
+        Value::LocalTimestampMillis(_) => "local-timestamp-millis".to_string(),
+        Value::LocalTimestampMicros(_) => "local-timestamp-micros".to_string(),
+        Value::Uuid(_) => "uuid".to_string(),
+        Value::Duration { .. } => "duration".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::Schema;
+    use crate::value::{Record, Value};
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn collects_violations_with_paths() {
+        let schema = Schema::from_str(
+            r##"{"type": "record", "name": "Customer", "fields": [
+                {"name": "id", "type": "long"},
+                {"name": "orders", "type": {"type": "array", "items": {
+                    "type": "record", "name": "Order", "fields": [
+                        {"name": "price", "type": "double"},
+                        {"name": "notes", "type": {"type": "map", "values": "string"}}
+                    ]}}}
+            ]}"##,
+        )
+        .unwrap();
+        let order = |price: Value| {
+            let mut order = Record::new("Order");
+            order.insert("price", price).unwrap();
+            order
+                .insert("notes", HashMap::<String, Value>::new())
+                .unwrap();
+            Value::Record(order)
+        };
+        let mut notes = HashMap::new();
+        notes.insert("gift".to_string(), Value::Int(1));
+        let mut bad_order = Record::new("Order");
+        bad_order.insert("price", 2.0).unwrap();
+        bad_order.insert("notes", notes).unwrap();
+        let mut customer = Record::new("Customer");
+        customer
+            .insert(
+                "orders",
+                vec![
+                    order(Value::Double(1.0)),
+                    order(Value::Str("free".to_string())),
+                    Value::Record(bad_order),
+                ],
+            )
+            .unwrap();
+
+        let found: Vec<_> = schema
+            .violations(&Value::Record(customer))
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "/id: expected long, found no value and no default",
+                "/orders/1/price: expected double, found string",
+                "/orders/2/notes/gift: expected string, found int",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_collections_and_unions() {
+        let schema = Schema::from_str(r##"{"type": "array", "items": "int"}"##).unwrap();
+        assert!(schema.violations(&Value::Array(vec![])).is_empty());
+        let schema = Schema::from_str(r##"{"type": "map", "values": "int"}"##).unwrap();
+        assert!(schema.violations(&Value::Map(HashMap::new())).is_empty());
+
+        let schema = Schema::from_str(r##"["null", "int"]"##).unwrap();
+        let found = schema.violations(&Value::Str("a".to_string()));
+        assert_eq!(
+            found[0].to_string(),
+            "/: expected one of [null, int], found string"
+        );
+    }
+}
@@ -12,7 +12,11 @@ pub(crate) struct SerdeReader<'de> {
 }
 
 impl<'de> SerdeReader<'de> {
-    pub(crate) fn new(inner: &'de Value) -> Self {
+    pub(crate) fn new(mut inner: &'de Value) -> Self {
+        // values written to a union are read as the value itself
+        while let Value::Union { value, .. } = inner {
+            inner = value;
+        }
         SerdeReader { inner }
     }
 }
@@ -35,12 +39,41 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut SerdeReader<'de> {
             Value::Bytes(ref bytes) => visitor.visit_borrowed_bytes(&bytes),
             Value::Array(items) => visitor.visit_seq(ArrayDeserializer::new(&items)),
             Value::Enum(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            Value::Decimal(d) => visitor.visit_string(d.to_string()),
+            Value::Uuid(ref v) => visitor.visit_borrowed_str(v),
+            Value::Date(v) | Value::TimeMillis(v) => visitor.visit_i32(*v),
+            Value::TimeMicros(v)
+            | Value::TimestampMillis(v)
+            | Value::TimestampMicros(v)
+            | Value::LocalTimestampMillis(v)
+            | Value::LocalTimestampMicros(v) => visitor.visit_i64(*v),
             _ => Err(AvrowErr::Unsupported),
         }
     }
 
     forward_to_deserialize_any! {
-        unit bool u8 i8 i16 i32 i64 u16 u32 u64 f32 f64 str bytes byte_buf string ignored_any enum
+        unit bool u8 i8 i16 i32 i64 u16 u32 u64 f32 f64 bytes byte_buf ignored_any enum
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // chrono types deserialize from their string form
+        #[cfg(feature = "chrono")]
+        {
+            if let Some(formatted) = crate::logical::format_temporal(self.inner) {
+                return visitor.visit_string(formatted?);
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -72,10 +105,6 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut SerdeReader<'de> {
                 let byte_seq_deser = ByteSeqDeserializer { input: buf.iter() };
                 visitor.visit_seq(byte_seq_deser)
             }
-            Value::Union(v) => match v.as_ref() {
-                Value::Array(ref items) => visitor.visit_seq(ArrayDeserializer::new(items)),
-                _ => Err(AvrowErr::Unsupported),
-            },
             _ => Err(AvrowErr::Unsupported),
         }
     }
@@ -125,10 +154,6 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut SerdeReader<'de> {
     {
         match self.inner {
             Value::Record(ref r) => visitor.visit_map(StructReader::new(r.fields.iter())),
-            Value::Union(ref inner) => match **inner {
-                Value::Record(ref rec) => visitor.visit_map(StructReader::new(rec.fields.iter())),
-                _ => Err(de::Error::custom("Union variant not a record/struct")),
-            },
             _ => Err(de::Error::custom("Must be a record/struct")),
         }
     }
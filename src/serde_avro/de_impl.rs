@@ -48,7 +48,7 @@ impl<'de> de::MapAccess<'de> for StructReader<'de> {
         if let Some(a) = a {
             match &a.value {
                 Value::Null => seed.deserialize(NullDeserializer),
-                value => seed.deserialize(&mut SerdeReader { inner: &value }),
+                value => seed.deserialize(&mut SerdeReader::new(value)),
             }
         } else {
             Err(de::Error::custom("Unexpected call to next_value_seed."))
@@ -1,5 +1,6 @@
 use super::ser_impl::{MapSerializer, SeqSerializer, StructSerializer};
 use crate::error::AvrowErr;
+use crate::logical::DECIMAL_NEWTYPE;
 use crate::value::Value;
 use serde::ser::{self, Serialize};
 
@@ -113,13 +114,16 @@ impl<'b> ser::Serializer for &'b mut SerdeWriter {
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        value.serialize(self)
+        match (name, value.serialize(self)?) {
+            (DECIMAL_NEWTYPE, Value::Str(s)) => Ok(Value::Decimal(s.parse()?)),
+            (_, value) => Ok(value),
+        }
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
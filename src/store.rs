@@ -0,0 +1,402 @@
+//! Contains the [SchemaStore](trait.SchemaStore.html) trait, used to look up the writer schema of
+//! avro data that is encoded without its schema, such as a
+//! [single object encoded](https://avro.apache.org/docs/current/spec.html#single_object_encoding) message.
+//! Implementations are provided for an in-memory store and for a directory of `.avsc` files.
+
+use crate::error::AvrowErr;
+use crate::schema::Schema;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// A store of schemas, used to look up the schema an avro message was written with.
+/// Schemas are identified by an id assigned by the store when they are registered, or by the
+/// rabin64 fingerprint of their canonical form.
+pub trait SchemaStore {
+    /// Returns the schema registered with the given id.
+    fn get_by_id(&self, id: u32) -> Result<Arc<Schema>, AvrowErr>;
+
+    /// Returns the schema whose canonical form has the given rabin64 fingerprint.
+    fn get_by_fingerprint(&self, fingerprint: i64) -> Result<Arc<Schema>, AvrowErr>;
+
+    /// Registers a schema and returns its id. Registering a schema that is already present in the
+    /// store returns the id of the existing schema.
+    fn register(&self, schema: Schema) -> Result<u32, AvrowErr>;
+}
+
+// Schemas indexed by their id and fingerprint.
+#[derive(Debug, Default)]
+pub(crate) struct Schemas {
+    by_id: HashMap<u32, Arc<Schema>>,
+    ids: HashMap<i64, u32>,
+}
+
+impl Schemas {
+    pub(crate) fn get_by_id(&self, id: u32) -> Result<Arc<Schema>, AvrowErr> {
+        self.by_id
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| AvrowErr::SchemaNotInStore(format!("id {}", id)))
+    }
+
+    pub(crate) fn get_by_fingerprint(&self, fingerprint: i64) -> Result<Arc<Schema>, AvrowErr> {
+        self.ids
+            .get(&fingerprint)
+            .and_then(|id| self.by_id.get(id))
+            .cloned()
+            .ok_or_else(|| AvrowErr::SchemaNotInStore(format!("fingerprint {}", fingerprint)))
+    }
+
+    pub(crate) fn id_of(&self, fingerprint: i64) -> Option<u32> {
+        self.ids.get(&fingerprint).cloned()
+    }
+
+    // The id following the highest id in use.
+    pub(crate) fn next_id(&self) -> u32 {
+        self.by_id.keys().max().map_or(1, |id| id + 1)
+    }
+
+    pub(crate) fn insert(&mut self, id: u32, schema: Arc<Schema>) {
+        self.ids.insert(schema.canonical_form().rabin64(), id);
+        self.by_id.insert(id, schema);
+    }
+}
+
+/// A schema store that keeps schemas in memory. Ids are assigned sequentially starting from 1.
+///
+/// ```rust
+/// use avrow::{InMemorySchemaStore, Schema, SchemaStore};
+/// use std::str::FromStr;
+///
+/// let store = InMemorySchemaStore::new();
+/// let id = store.register(Schema::from_str(r##""string""##).unwrap()).unwrap();
+/// assert_eq!(id, 1);
+/// assert!(store.get_by_id(id).is_ok());
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemorySchemaStore {
+    schemas: RwLock<Schemas>,
+}
+
+impl InMemorySchemaStore {
+    /// Creates an empty in-memory schema store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn schemas(&self) -> Result<std::sync::RwLockReadGuard<'_, Schemas>, AvrowErr> {
+        self.schemas
+            .read()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)
+    }
+}
+
+impl SchemaStore for InMemorySchemaStore {
+    fn get_by_id(&self, id: u32) -> Result<Arc<Schema>, AvrowErr> {
+        self.schemas()?.get_by_id(id)
+    }
+
+    fn get_by_fingerprint(&self, fingerprint: i64) -> Result<Arc<Schema>, AvrowErr> {
+        self.schemas()?.get_by_fingerprint(fingerprint)
+    }
+
+    fn register(&self, schema: Schema) -> Result<u32, AvrowErr> {
+        let mut schemas = self
+            .schemas
+            .write()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)?;
+        if let Some(id) = schemas.id_of(schema.canonical_form().rabin64()) {
+            return Ok(id);
+        }
+        let id = schemas.next_id();
+        schemas.insert(id, Arc::new(schema));
+        Ok(id)
+    }
+}
+
+/// A schema store backed by a directory of `.avsc` files. Registering a schema writes it to a new
+/// file in the directory named `{id}-{fingerprint}.avsc`, so that its id is kept when the store
+/// is opened again.
+#[derive(Debug)]
+pub struct DirectorySchemaStore {
+    dir: PathBuf,
+    schemas: RwLock<Schemas>,
+}
+
+impl DirectorySchemaStore {
+    /// Opens a store on the given directory, loading all `.avsc` files in it. Files that are not
+    /// named after an id and the fingerprint of their schema, such as schemas copied into the
+    /// directory, are assigned the next free ids in the order of their file names and are written
+    /// to the directory under their id.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, AvrowErr> {
+        let dir = dir.as_ref().to_path_buf();
+        let mut numbered = vec![];
+        let mut unnumbered = vec![];
+        for entry in fs::read_dir(&dir).map_err(AvrowErr::SchemaStoreIo)? {
+            let path = entry.map_err(AvrowErr::SchemaStoreIo)?.path();
+            if path.extension() != Some(OsStr::new("avsc")) {
+                continue;
+            }
+            match parse_file_name(&path) {
+                Some((id, fingerprint)) => numbered.push((id, fingerprint, path)),
+                None => unnumbered.push(path),
+            }
+        }
+        numbered.sort();
+
+        let store = DirectorySchemaStore {
+            dir,
+            schemas: RwLock::new(Schemas::default()),
+        };
+        {
+            let mut schemas = store.write()?;
+            for (id, fingerprint, path) in numbered {
+                let schema = Schema::from_path(&path)?;
+                if schema.canonical_form().rabin64() != fingerprint {
+                    unnumbered.push(path);
+                    continue;
+                }
+                match schemas.get_by_id(id) {
+                    Ok(existing) if *existing != schema => {
+                        return Err(AvrowErr::SchemaStoreIo(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("more than one schema with id {} in the store", id),
+                        )))
+                    }
+                    Ok(_) => {}
+                    Err(_) => schemas.insert(id, Arc::new(schema)),
+                }
+            }
+        }
+        unnumbered.sort();
+        for path in unnumbered {
+            store.register(Schema::from_path(&path)?)?;
+        }
+        Ok(store)
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Schemas>, AvrowErr> {
+        self.schemas
+            .read()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Schemas>, AvrowErr> {
+        self.schemas
+            .write()
+            .map_err(|_| AvrowErr::SchemaStorePoisoned)
+    }
+}
+
+// Parses the id and fingerprint of a schema file named `{id}-{fingerprint:016x}.avsc`.
+fn parse_file_name(path: &Path) -> Option<(u32, i64)> {
+    let stem = path.file_stem()?.to_str()?;
+    let (id, fingerprint) = stem.split_once('-')?;
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if fingerprint.len() != 16 || !fingerprint.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let fingerprint = u64::from_str_radix(fingerprint, 16).ok()? as i64;
+    Some((id.parse().ok()?, fingerprint))
+}
+
+impl SchemaStore for DirectorySchemaStore {
+    fn get_by_id(&self, id: u32) -> Result<Arc<Schema>, AvrowErr> {
+        self.read()?.get_by_id(id)
+    }
+
+    fn get_by_fingerprint(&self, fingerprint: i64) -> Result<Arc<Schema>, AvrowErr> {
+        self.read()?.get_by_fingerprint(fingerprint)
+    }
+
+    fn register(&self, schema: Schema) -> Result<u32, AvrowErr> {
+        let fingerprint = schema.canonical_form().rabin64();
+        let mut schemas = self.write()?;
+        if let Some(id) = schemas.id_of(fingerprint) {
+            return Ok(id);
+        }
+        let id = schemas.next_id();
+        let path = self.dir.join(format!("{}-{:016x}.avsc", id, fingerprint));
+        fs::write(path, schema.to_string()).map_err(AvrowErr::SchemaStoreIo)?;
+        schemas.insert(id, Arc::new(schema));
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DirectorySchemaStore, InMemorySchemaStore, SchemaStore};
+    use crate::schema::Schema;
+    use std::str::FromStr;
+
+    #[test]
+    fn in_memory_store_register_and_get() {
+        let store = InMemorySchemaStore::new();
+        let schema = Schema::from_str(r##""string""##).unwrap();
+        let fingerprint = schema.canonical_form().rabin64();
+
+        assert_eq!(store.register(schema).unwrap(), 1);
+        let schema = Schema::from_str(r##"{"type": "string"}"##).unwrap();
+        assert_eq!(store.register(schema).unwrap(), 1);
+        assert_eq!(
+            store
+                .register(Schema::from_str(r##""int""##).unwrap())
+                .unwrap(),
+            2
+        );
+
+        assert_eq!(
+            *store.get_by_fingerprint(fingerprint).unwrap(),
+            Schema::from_str(r##""string""##).unwrap()
+        );
+        assert!(store.get_by_id(3).is_err());
+        assert!(store.get_by_fingerprint(0).is_err());
+    }
+
+    #[test]
+    fn directory_store_persists_registered_schemas() {
+        let dir = std::env::temp_dir().join(format!("avrow-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.avsc"), r##"{"type": "int"}"##).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a schema").unwrap();
+
+        let store = DirectorySchemaStore::open(&dir).unwrap();
+        let int_schema = Schema::from_str(r##""int""##).unwrap();
+        assert!(store
+            .get_by_fingerprint(int_schema.canonical_form().rabin64())
+            .is_ok());
+
+        let long_schema = Schema::from_str(r##""long""##).unwrap();
+        let fingerprint = long_schema.canonical_form().rabin64();
+        assert_eq!(store.register(long_schema).unwrap(), 2);
+
+        let reopened = DirectorySchemaStore::open(&dir).unwrap();
+        assert!(reopened.get_by_fingerprint(fingerprint).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn directory_store_keeps_ids_when_reopened() {
+        let dir = std::env::temp_dir().join(format!("avrow-store-ids-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.avsc"), r##""int""##).unwrap();
+
+        let schemas = [
+            r##""long""##,
+            r##""string""##,
+            r##""bytes""##,
+            r##""double""##,
+        ];
+        let store = DirectorySchemaStore::open(&dir).unwrap();
+        let ids: Vec<_> = schemas
+            .iter()
+            .map(|s| store.register(Schema::from_str(s).unwrap()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4, 5]);
+        // a schema copied in later whose file name sorts first
+        std::fs::write(dir.join("a.avsc"), r##""float""##).unwrap();
+
+        for _ in 0..2 {
+            let reopened = DirectorySchemaStore::open(&dir).unwrap();
+            let int_schema = Schema::from_str(r##""int""##).unwrap();
+            assert_eq!(reopened.register(int_schema).unwrap(), 1);
+            for (schema, id) in schemas.iter().zip(&ids) {
+                assert_eq!(
+                    *reopened.get_by_id(*id).unwrap(),
+                    Schema::from_str(schema).unwrap()
+                );
+            }
+            let float_schema = Schema::from_str(r##""float""##).unwrap();
+            assert_eq!(reopened.register(float_schema).unwrap(), 6);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn directory_store_checks_file_names() {
+        let dir = std::env::temp_dir().join(format!("avrow-store-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let long_schema = Schema::from_str(r##""long""##).unwrap();
+        let fingerprint = long_schema.canonical_form().rabin64();
+        std::fs::write(
+            dir.join(format!("3-{:016x}.avsc", fingerprint)),
+            r##""long""##,
+        )
+        .unwrap();
+        // not named after the fingerprint of their schema
+        std::fs::write(dir.join("7-backup.avsc"), r##""int""##).unwrap();
+        std::fs::write(dir.join("8-0000000000000000.avsc"), r##""string""##).unwrap();
+        std::fs::write(
+            dir.join(format!("+9-{:016x}.avsc", fingerprint)),
+            r##""long""##,
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            let store = DirectorySchemaStore::open(&dir).unwrap();
+            let long_schema = Schema::from_str(r##""long""##).unwrap();
+            assert_eq!(store.register(long_schema).unwrap(), 3);
+            let int_schema = Schema::from_str(r##""int""##).unwrap();
+            assert_eq!(store.register(int_schema).unwrap(), 4);
+            let string_schema = Schema::from_str(r##""string""##).unwrap();
+            assert_eq!(store.register(string_schema).unwrap(), 5);
+            assert!(store.get_by_id(7).is_err());
+            assert!(store.get_by_id(8).is_err());
+            assert!(store.get_by_id(9).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reader_uses_schema_from_store() {
+        use crate::{LogicalType, Reader, Value, Writer};
+
+        #[derive(Debug)]
+        struct Upper;
+
+        impl LogicalType for Upper {
+            fn name(&self) -> &str {
+                "upper"
+            }
+
+            fn to_underlying(&self, value: &Value) -> Result<Value, crate::AvrowErr> {
+                Ok(value.clone())
+            }
+
+            fn to_logical(&self, value: Value) -> Result<Value, crate::AvrowErr> {
+                match value {
+                    Value::Str(s) => Ok(Value::Str(s.to_uppercase())),
+                    other => Ok(other),
+                }
+            }
+        }
+
+        let schema_str = r##"{"type": "string", "logicalType": "upper"}"##;
+        let schema = Schema::from_str(schema_str).unwrap();
+        let mut writer = Writer::new(&schema, vec![]).unwrap();
+        writer.write("avrow").unwrap();
+        let buf = writer.into_inner().unwrap();
+
+        let store = InMemorySchemaStore::new();
+        let reader = Reader::with_store(buf.as_slice(), &store).unwrap();
+        for i in reader {
+            assert_eq!(i.unwrap(), Value::Str("avrow".to_string()));
+        }
+
+        let mut schema = Schema::from_str(schema_str).unwrap();
+        schema.register_logical_type(Upper).unwrap();
+        store.register(schema).unwrap();
+        let reader = Reader::with_store(buf.as_slice(), &store).unwrap();
+        for i in reader {
+            assert_eq!(i.unwrap(), Value::Str("AVROW".to_string()));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Represents the types that
 
 use crate::error::AvrowErr;
+use crate::logical::{duration_to_bytes, parse_uuid, sign_extend, Decimal};
 use crate::schema;
 use crate::schema::common::validate_name;
 use crate::schema::parser::parse_default;
@@ -191,14 +192,120 @@ pub enum Value {
     Bytes(Vec<u8>),
     /// Rust strings map directly to avro strings
     Str(String),
-    /// A union is a sequence of unique `Value`s
-    Union(Box<Value>),
+    /// A value written to a union. Other values written to a union are written as the first
+    /// branch they match, while the branch of a `Union` can also be given explicitly, e.g. to write
+    /// a string as a `bytes` branch.
+    Union {
+        /// The branch of the union, by its index or type name. If `None`, the branch is picked by
+        /// the value.
+        branch: Option<UnionBranch>,
+        /// The value of the branch.
+        value: Box<Value>,
+    },
     /// An enumeration. Unlike Rust enums, enums in avro don't support data within their variants.
     Enum(String),
     /// An array of `Value`s
     Array(Vec<Value>),
     /// auxiliary u8 helper for serde. Not an avro value.
     Byte(u8),
+    /// A decimal logical value, encoded as `bytes` or `fixed`.
+    Decimal(Decimal),
+    /// A date logical value, as the number of days since the unix epoch.
+    Date(i32),
+    /// A time logical value, as the number of milliseconds after midnight.
+    TimeMillis(i32),
+    /// A time logical value, as the number of microseconds after midnight.
+    TimeMicros(i64),
+    /// A timestamp logical value, as the number of milliseconds since the unix epoch in UTC.
+    TimestampMillis(i64),
+    /// A timestamp logical value, as the number of microseconds since the unix epoch in UTC.
+    TimestampMicros(i64),
+    /// A timestamp logical value in local time, as the number of milliseconds since the unix epoch.
+    LocalTimestampMillis(i64),
+    /// A timestamp logical value in local time, as the number of microseconds since the unix epoch.
+    LocalTimestampMicros(i64),
+    /// A uuid logical value, in its hyphenated string form.
+    Uuid(String),
+    /// A duration logical value, encoded as a `fixed` of size 12.
+    Duration {
+        /// Number of months.
+        months: u32,
+        /// Number of days.
+        days: u32,
+        /// Number of milliseconds.
+        millis: u32,
+    },
+}
+
+/// Selects the branch of a union that a [`Value::Union`](enum.Value.html#variant.Union) is written as.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum UnionBranch {
+    /// The position of the branch in the union schema.
+    Index(usize),
+    /// The type name of the branch. This is the fullname (or name) for records, enums and fixed types,
+    /// the logical type name for logical types, and the type name for any other type, e.g `long` or `map`.
+    Name(String),
+}
+
+impl UnionBranch {
+    // Returns the index of this branch in the union, given its resolved branches.
+    pub(crate) fn position(&self, branches: &[&Variant]) -> Option<usize> {
+        match self {
+            UnionBranch::Index(idx) if *idx < branches.len() => Some(*idx),
+            UnionBranch::Index(_) => None,
+            UnionBranch::Name(name) => branches.iter().position(|b| is_named(b, name)),
+        }
+    }
+}
+
+impl Display for UnionBranch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnionBranch::Index(idx) => write!(f, "{}", idx),
+            UnionBranch::Name(name) => write!(f, "`{}`", name),
+        }
+    }
+}
+
+fn is_named(branch: &Variant, type_name: &str) -> bool {
+    let name = match branch {
+        Variant::Record { name, .. } | Variant::Enum { name, .. } | Variant::Fixed { name, .. } => {
+            return name.fullname() == type_name || name.name == type_name
+        }
+        Variant::Custom { logical, .. } => return logical.0.name() == type_name,
+        Variant::Null => "null",
+        Variant::Boolean => "boolean",
+        Variant::Int => "int",
+        Variant::Long => "long",
+        Variant::Float => "float",
+        Variant::Double => "double",
+        Variant::Bytes => "bytes",
+        Variant::Str => "string",
+        Variant::Array { .. } => "array",
+        Variant::Map { .. } => "map",
+        Variant::Union { .. } => "union",
+        Variant::Decimal { .. } => "decimal",
+        Variant::Uuid { .. } => "uuid",
+        Variant::Duration { .. } => "duration",
+        Variant::Date => "date",
+        Variant::TimeMillis => "time-millis",
+        Variant::TimeMicros => "time-micros",
+        Variant::TimestampMillis => "timestamp-millis",
+        Variant::TimestampMicros => "timestamp-micros",
+        Variant::LocalTimestampMillis => "local-timestamp-millis",
+        Variant::LocalTimestampMicros => "local-timestamp-micros",
+        Variant::Named(name) => name,
+    };
+    name == type_name
+}
+
+// Options that change how values are encoded, configured through the WriterBuilder.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EncodeOptions {
+    // Write arrays and maps as blocks prefixed with their size in bytes.
+    pub(crate) sized_blocks: bool,
+    // Reject records having fields that are not in their schema, instead of ignoring them.
+    pub(crate) strict_records: bool,
 }
 
 impl Value {
@@ -207,6 +314,16 @@ impl Value {
         writer: &mut W,
         schema: &Variant,
         cxt: &Registry,
+    ) -> Result<(), AvrowErr> {
+        self.encode_with(writer, schema, cxt, EncodeOptions::default())
+    }
+
+    pub(crate) fn encode_with<W: Write>(
+        &self,
+        writer: &mut W,
+        schema: &Variant,
+        cxt: &Registry,
+        options: EncodeOptions,
     ) -> Result<(), AvrowErr> {
         match (self, schema) {
             (Value::Null, Variant::Null) => {}
@@ -264,7 +381,7 @@ impl Value {
             }
             (ref value, Variant::Named(name)) => {
                 if let Some(schema) = cxt.get(name) {
-                    value.encode(writer, schema, cxt)?;
+                    value.encode_with(writer, schema, cxt, options)?;
                 }
             }
             // Match with union happens first than more specific match arms
@@ -274,25 +391,133 @@ impl Value {
                 writer
                     .write_varint(union_idx)
                     .map_err(AvrowErr::EncodeFailed)?;
-                value.encode(writer, &schema, cxt)?
+                match value {
+                    Value::Union { value, .. } => {
+                        value.encode_with(writer, schema, cxt, options)?
+                    }
+                    value => value.encode_with(writer, schema, cxt, options)?,
+                }
+            }
+            (
+                Value::Decimal(d),
+                Variant::Decimal {
+                    precision,
+                    scale,
+                    inner,
+                },
+            ) => {
+                let bytes = d.to_schema_bytes(*precision, *scale)?;
+                if let Variant::Fixed { size, .. } = **inner {
+                    encode_raw_bytes(&sign_extend(&bytes, size)?, writer)?;
+                } else {
+                    encode_long(bytes.len() as i64, writer)?;
+                    encode_raw_bytes(&bytes, writer)?;
+                }
+            }
+            // decimals serialized via serde arrive as strings
+            (Value::Str(s), Variant::Decimal { .. }) => {
+                Value::Decimal(s.parse()?).encode_with(writer, schema, cxt, options)?;
             }
-            (Value::Record(ref record), Variant::Record { fields, .. }) => {
-                for (f_name, f_value) in &record.fields {
-                    let field_type = fields.get(f_name);
-                    if let Some(field_ty) = field_type {
-                        f_value.value.encode(writer, &field_ty.ty, cxt)?;
+            // the underlying bytes of a decimal can also be written as is
+            (value, Variant::Decimal { inner, .. }) => {
+                value.encode_with(writer, inner, cxt, options)?
+            }
+            (Value::Uuid(s), Variant::Uuid { inner }) => match **inner {
+                Variant::Fixed { .. } => encode_raw_bytes(&parse_uuid(s)?, writer)?,
+                _ => {
+                    let _ = parse_uuid(s)?;
+                    encode_long(s.len() as i64, writer)?;
+                    encode_raw_bytes(s.as_bytes(), writer)?;
+                }
+            },
+            // uuids serialized via serde arrive as strings
+            (Value::Str(s), Variant::Uuid { .. }) => {
+                Value::Uuid(s.clone()).encode_with(writer, schema, cxt, options)?
+            }
+            (value, Variant::Uuid { inner }) => value.encode_with(writer, inner, cxt, options)?,
+            (
+                Value::Duration {
+                    months,
+                    days,
+                    millis,
+                },
+                Variant::Duration { .. },
+            ) => encode_raw_bytes(&duration_to_bytes(*months, *days, *millis), writer)?,
+            (value, Variant::Duration { inner }) => {
+                value.encode_with(writer, inner, cxt, options)?
+            }
+            (value, Variant::Custom { logical, inner }) => logical
+                .0
+                .to_underlying(value)?
+                .encode_with(writer, inner, cxt, options)?,
+            (Value::Date(v), Variant::Date) | (Value::TimeMillis(v), Variant::TimeMillis) => {
+                writer.write_varint(*v).map_err(AvrowErr::EncodeFailed)?;
+            }
+            (Value::TimeMicros(v), Variant::TimeMicros)
+            | (Value::TimestampMillis(v), Variant::TimestampMillis)
+            | (Value::TimestampMicros(v), Variant::TimestampMicros)
+            | (Value::LocalTimestampMillis(v), Variant::LocalTimestampMillis)
+            | (Value::LocalTimestampMicros(v), Variant::LocalTimestampMicros) => {
+                writer.write_varint(*v).map_err(AvrowErr::EncodeFailed)?;
+            }
+            // chrono types serialized via serde arrive as strings
+            #[cfg(feature = "chrono")]
+            (Value::Str(s), v) if v.temporal_underlying().is_some() => {
+                crate::logical::parse_temporal(s, v)?.encode_with(writer, v, cxt, options)?
+            }
+            (value, v) if v.temporal_underlying().is_some() => {
+                match crate::logical::convert_temporal(value, v) {
+                    Some(temporal) => temporal?.encode_with(writer, v, cxt, options)?,
+                    // temporal types can also be written as their underlying int or long
+                    None => {
+                        let underlying = v.temporal_underlying().unwrap();
+                        value.encode_with(writer, &underlying, cxt, options)?
                     }
                 }
             }
+            (Value::Record(ref record), Variant::Record { name, fields, .. }) => {
+                if options.strict_records {
+                    if let Some(f_name) = record.fields.keys().find(|f| !fields.contains_key(*f)) {
+                        return Err(AvrowErr::UnknownRecordField {
+                            record: name.fullname(),
+                            field: f_name.to_string(),
+                        });
+                    }
+                }
+                // fields are written in the order of the schema
+                for (f_name, field) in fields {
+                    let value = match (record.fields.get(f_name), &field.default) {
+                        (Some(f_value), _) => &f_value.value,
+                        (None, Some(default)) => default,
+                        (None, None) => {
+                            return Err(AvrowErr::MissingRecordField {
+                                record: name.fullname(),
+                                field: f_name.to_string(),
+                            })
+                        }
+                    };
+                    value.encode_with(writer, &field.ty, cxt, options)?;
+                }
+            }
             (Value::Map(hmap), Variant::Map { values }) => {
-                // number of keys/value (start of a block)
-                encode_long(hmap.keys().len() as i64, writer)?;
-                for (k, v) in hmap.iter() {
-                    encode_long(k.len() as i64, writer)?;
-                    encode_raw_bytes(&*k.as_bytes(), writer)?;
-                    v.encode(writer, values, cxt)?;
+                if options.sized_blocks {
+                    let mut block = vec![];
+                    for (k, v) in hmap.iter() {
+                        encode_long(k.len() as i64, &mut block)?;
+                        encode_raw_bytes(k.as_bytes(), &mut block)?;
+                        v.encode_with(&mut block, values, cxt, options)?;
+                    }
+                    encode_sized_block(hmap.len(), &block, writer)?;
+                } else if !hmap.is_empty() {
+                    // number of keys/value (start of a block)
+                    encode_long(hmap.keys().len() as i64, writer)?;
+                    for (k, v) in hmap.iter() {
+                        encode_long(k.len() as i64, writer)?;
+                        encode_raw_bytes(k.as_bytes(), writer)?;
+                        v.encode_with(writer, values, cxt, options)?;
+                    }
                 }
-                // marks end of block
+                // marks end of the map
                 encode_long(0, writer)?;
             }
             (Value::Fixed(ref v), Variant::Fixed { .. }) => {
@@ -334,13 +559,20 @@ impl Value {
                     items: items_schema,
                 },
             ) => {
-                let array_items_count = Value::from(values.len() as i64);
-                array_items_count.encode(writer, &Variant::Long, cxt)?;
-
-                for i in values {
-                    i.encode(writer, items_schema, cxt)?;
+                if options.sized_blocks {
+                    let mut block = vec![];
+                    for i in values {
+                        i.encode_with(&mut block, items_schema, cxt, options)?;
+                    }
+                    encode_sized_block(values.len(), &block, writer)?;
+                } else if !values.is_empty() {
+                    encode_long(values.len() as i64, writer)?;
+                    for i in values {
+                        i.encode_with(writer, items_schema, cxt, options)?;
+                    }
                 }
-                Value::from(0i64).encode(writer, &Variant::Long, cxt)?;
+                // marks end of the array
+                encode_long(0, writer)?;
             }
             // case where serde serializes a Vec<u8> to a Array of Byte
             // FIXME:figure out a better way for this?
@@ -360,63 +592,88 @@ impl Value {
     }
 }
 
-// Given a value, returns the index and the variant of the union
+// Writes the encoded items of an array or a map as a block with a negative item count,
+// followed by the size of the block in bytes, so that readers can skip the block.
+fn encode_sized_block<W: Write>(
+    count: usize,
+    block: &[u8],
+    writer: &mut W,
+) -> Result<(), AvrowErr> {
+    if count > 0 {
+        encode_long(-(count as i64), writer)?;
+        encode_long(block.len() as i64, writer)?;
+        encode_raw_bytes(block, writer)?;
+    }
+    Ok(())
+}
+
+// Given a value, returns the index and the variant of the union branch it gets written as.
+// A value for an explicit branch is written as that branch. Otherwise records are matched to
+// a branch by name, enums by their symbol and fixed values by size. Any other value is matched
+// to the first branch of the same type, or else to the first branch its type can be promoted to.
 fn resolve_union<'a>(
     value: &Value,
     union_variants: &'a [Variant],
     cxt: &'a Registry,
 ) -> Result<(usize, &'a Variant), AvrowErr> {
-    for (idx, variant) in union_variants.iter().enumerate() {
-        match (value, variant) {
-            (Value::Null, Variant::Null)
-            | (Value::Boolean(_), Variant::Boolean)
-            | (Value::Int(_), Variant::Int)
-            | (Value::Long(_), Variant::Long)
-            | (Value::Float(_), Variant::Float)
-            | (Value::Double(_), Variant::Double)
-            | (Value::Bytes(_), Variant::Bytes)
-            | (Value::Str(_), Variant::Str)
-            | (Value::Map(_), Variant::Map { .. })
-            | (Value::Array(_), Variant::Array { .. })
-            | (Value::Fixed(_), Variant::Fixed { .. })
-            | (Value::Enum(_), Variant::Enum { .. })
-            | (Value::Record(_), Variant::Record { .. }) => return Ok((idx, variant)),
-            (Value::Array(v), Variant::Fixed { size, .. }) => {
-                if v.len() == *size {
-                    return Ok((idx, variant));
-                }
-                return Err(AvrowErr::FixedValueLenMismatch {
-                    found: v.len(),
-                    expected: *size,
-                });
-            }
-            (Value::Union(_), _) => return Err(AvrowErr::NoImmediateUnion),
-            (Value::Record(_), Variant::Named(name)) => {
-                if let Some(schema) = cxt.get(&name) {
-                    return Ok((idx, schema));
-                } else {
-                    return Err(AvrowErr::SchemaNotFoundInUnion);
-                }
-            }
-            (Value::Enum(_), Variant::Named(name)) => {
-                if let Some(schema) = cxt.get(&name) {
-                    return Ok((idx, schema));
-                } else {
-                    return Err(AvrowErr::SchemaNotFoundInUnion);
-                }
-            }
-            (Value::Fixed(_), Variant::Named(name)) => {
-                if let Some(schema) = cxt.get(&name) {
-                    return Ok((idx, schema));
-                } else {
-                    return Err(AvrowErr::SchemaNotFoundInUnion);
-                }
-            }
-            _a => {}
+    let branches: Vec<&Variant> = union_variants
+        .iter()
+        .map(|variant| match variant {
+            Variant::Named(name) => cxt.get(name).unwrap_or(variant),
+            _ => variant,
+        })
+        .collect();
+    let (branch, value) = match value {
+        Value::Union { branch, value } => (branch.as_ref(), value.as_ref()),
+        value => (None, value),
+    };
+    let by_name = |branch: &Variant| match (value, branch) {
+        (Value::Record(rec), Variant::Record { name, .. }) => {
+            name.fullname() == rec.name || name.name == rec.name
         }
-    }
+        (Value::Enum(sym), Variant::Enum { symbols, .. }) => symbols.contains(sym),
+        (Value::Fixed(v), Variant::Fixed { size, .. }) => v.len() == *size,
+        _ => false,
+    };
+    let exact = |branch: &Variant| match (value, branch) {
+        (Value::Null, Variant::Null)
+        | (Value::Boolean(_), Variant::Boolean)
+        | (Value::Int(_), Variant::Int)
+        | (Value::Long(_), Variant::Long)
+        | (Value::Float(_), Variant::Float)
+        | (Value::Double(_), Variant::Double)
+        | (Value::Bytes(_), Variant::Bytes)
+        | (Value::Str(_), Variant::Str)
+        | (Value::Map(_), Variant::Map { .. })
+        | (Value::Array(_), Variant::Array { .. })
+        | (Value::Record(_), Variant::Record { .. })
+        | (Value::Decimal(_), Variant::Decimal { .. })
+        | (Value::Date(_), Variant::Date)
+        | (Value::TimeMillis(_), Variant::TimeMillis)
+        | (Value::TimeMicros(_), Variant::TimeMicros)
+        | (Value::TimestampMillis(_), Variant::TimestampMillis)
+        | (Value::TimestampMicros(_), Variant::TimestampMicros)
+        | (Value::LocalTimestampMillis(_), Variant::LocalTimestampMillis)
+        | (Value::LocalTimestampMicros(_), Variant::LocalTimestampMicros)
+        | (Value::Uuid(_), Variant::Uuid { .. })
+        | (Value::Duration { .. }, Variant::Duration { .. }) => true,
+        (_, Variant::Custom { .. }) => branch.validate(value, cxt).is_ok(),
+        _ => false,
+    };
+    // covers promotions of primitives and the string forms of logical types
+    let promoted = |branch: &Variant| branch.validate(value, cxt).is_ok();
 
-    Err(AvrowErr::SchemaNotFoundInUnion)
+    let idx = match (branch, value) {
+        (_, Value::Union { .. }) => return Err(AvrowErr::NoImmediateUnion),
+        (Some(branch), _) => branch.position(&branches),
+        (None, _) => branches
+            .iter()
+            .position(|b| by_name(b))
+            .or_else(|| branches.iter().position(|b| exact(b)))
+            .or_else(|| branches.iter().position(|b| promoted(b))),
+    };
+    idx.map(|idx| (idx, branches[idx]))
+        .ok_or(AvrowErr::SchemaNotFoundInUnion)
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -521,6 +778,54 @@ impl<'a> From<&'a str> for Value {
     }
 }
 
+impl From<Decimal> for Value {
+    fn from(value: Decimal) -> Value {
+        Value::Decimal(value)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Decimal> for Value {
+    fn from(value: rust_decimal::Decimal) -> Value {
+        Value::Decimal(value.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for Value {
+    fn from(value: chrono::NaiveDate) -> Value {
+        Value::Date(crate::logical::days_since_epoch(value))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveTime> for Value {
+    fn from(value: chrono::NaiveTime) -> Value {
+        Value::TimeMicros(crate::logical::micros_since_midnight(value))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for Value {
+    fn from(value: chrono::NaiveDateTime) -> Value {
+        Value::LocalTimestampMicros(crate::logical::micros_since_epoch(value))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Value {
+    fn from(value: chrono::DateTime<Tz>) -> Value {
+        Value::TimestampMicros(crate::logical::micros_since_epoch(value.naive_utc()))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Value {
+    fn from(value: uuid::Uuid) -> Value {
+        Value::Uuid(value.to_string())
+    }
+}
+
 #[macro_export]
 /// Convenient macro to create a avro fixed value
 macro_rules! fixed {
@@ -632,8 +937,8 @@ impl Value {
     }
     /// Try to retrieve an avro union
     pub fn as_union(&self) -> Result<&Value, AvrowErr> {
-        if let Value::Union(v) = self {
-            Ok(v)
+        if let Value::Union { value, .. } = self {
+            Ok(value)
         } else {
             Err(AvrowErr::ExpectedVariantNotFound)
         }
@@ -646,6 +951,91 @@ impl Value {
             Err(AvrowErr::ExpectedVariantNotFound)
         }
     }
+    /// Try to retrieve an avro decimal
+    pub fn as_decimal(&self) -> Result<&Decimal, AvrowErr> {
+        if let Value::Decimal(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro uuid as its hyphenated string form
+    pub fn as_uuid(&self) -> Result<&str, AvrowErr> {
+        if let Value::Uuid(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro duration as a tuple of months, days and milliseconds
+    pub fn as_duration(&self) -> Result<(u32, u32, u32), AvrowErr> {
+        if let Value::Duration {
+            months,
+            days,
+            millis,
+        } = self
+        {
+            Ok((*months, *days, *millis))
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro date as days since the unix epoch
+    pub fn as_date(&self) -> Result<&i32, AvrowErr> {
+        if let Value::Date(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro time-millis as milliseconds after midnight
+    pub fn as_time_millis(&self) -> Result<&i32, AvrowErr> {
+        if let Value::TimeMillis(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro time-micros as microseconds after midnight
+    pub fn as_time_micros(&self) -> Result<&i64, AvrowErr> {
+        if let Value::TimeMicros(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro timestamp-millis as milliseconds since the unix epoch
+    pub fn as_timestamp_millis(&self) -> Result<&i64, AvrowErr> {
+        if let Value::TimestampMillis(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro timestamp-micros as microseconds since the unix epoch
+    pub fn as_timestamp_micros(&self) -> Result<&i64, AvrowErr> {
+        if let Value::TimestampMicros(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro local-timestamp-millis as milliseconds since the unix epoch
+    pub fn as_local_timestamp_millis(&self) -> Result<&i64, AvrowErr> {
+        if let Value::LocalTimestampMillis(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
+    /// Try to retrieve an avro local-timestamp-micros as microseconds since the unix epoch
+    pub fn as_local_timestamp_micros(&self) -> Result<&i64, AvrowErr> {
+        if let Value::LocalTimestampMicros(v) = self {
+            Ok(v)
+        } else {
+            Err(AvrowErr::ExpectedVariantNotFound)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,22 +1,27 @@
 //! The Writer is the primary interface for writing values in avro encoded format.
 
-use crate::codec::Codec;
+#[cfg(feature = "zstd")]
+use crate::codec::ZstdOptions;
+use crate::codec::{BlockCompression, Codec, CodecRegistry};
 use crate::config::{DEFAULT_FLUSH_INTERVAL, MAGIC_BYTES, SYNC_MARKER_SIZE};
 use crate::error::{AvrowErr, AvrowResult};
+use crate::pool::WorkerPool;
 use crate::schema::Registry;
 use crate::schema::Schema;
 use crate::schema::Variant;
 use crate::serde_avro;
 use crate::util::{encode_long, encode_raw_bytes};
-use crate::value::Map;
 use crate::value::Value;
+use crate::value::{EncodeOptions, Map};
 use rand::{thread_rng, Rng};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::default::Default;
 use std::io::Write;
+#[cfg(feature = "zstd")]
+use std::sync::Arc;
 
-fn sync_marker() -> [u8; SYNC_MARKER_SIZE] {
+pub(crate) fn sync_marker() -> [u8; SYNC_MARKER_SIZE] {
     let mut vec = [0u8; SYNC_MARKER_SIZE];
     thread_rng().fill_bytes(&mut vec[..]);
     vec
@@ -26,9 +31,16 @@ fn sync_marker() -> [u8; SYNC_MARKER_SIZE] {
 pub struct WriterBuilder<'a, W> {
     metadata: HashMap<String, Value>,
     codec: Codec,
+    codecs: CodecRegistry,
+    custom_codec: Option<String>,
     schema: Option<&'a Schema>,
     datafile: Option<W>,
     flush_interval: usize,
+    encode_options: EncodeOptions,
+    #[cfg(feature = "zstd")]
+    zstd_options: ZstdOptions,
+    compression_threads: usize,
+    max_blocks_in_flight: Option<usize>,
 }
 
 impl<'a, W: Write> WriterBuilder<'a, W> {
@@ -37,9 +49,16 @@ impl<'a, W: Write> WriterBuilder<'a, W> {
         WriterBuilder {
             metadata: Default::default(),
             codec: Codec::Null,
+            codecs: CodecRegistry::new(),
+            custom_codec: None,
             schema: None,
             datafile: None,
             flush_interval: DEFAULT_FLUSH_INTERVAL,
+            encode_options: EncodeOptions::default(),
+            #[cfg(feature = "zstd")]
+            zstd_options: ZstdOptions::default(),
+            compression_threads: 0,
+            max_blocks_in_flight: None,
         }
     }
 
@@ -56,6 +75,20 @@ impl<'a, W: Write> WriterBuilder<'a, W> {
         self
     }
 
+    /// Set the registry of user defined codecs, from which a codec can be picked with
+    /// [`set_custom_codec`](struct.WriterBuilder.html#method.set_custom_codec).
+    pub fn set_codec_registry(mut self, codecs: CodecRegistry) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Set a user defined codec by its name in the codec registry. This takes precedence over
+    /// the codec set with [`set_codec`](struct.WriterBuilder.html#method.set_codec).
+    pub fn set_custom_codec(mut self, name: &str) -> Self {
+        self.custom_codec = Some(name.to_string());
+        self
+    }
+
     /// Provide the writer with a reference to the schema file.
     pub fn set_schema(mut self, schema: &'a Schema) -> Self {
         self.schema = Some(schema);
@@ -76,16 +109,126 @@ impl<'a, W: Write> WriterBuilder<'a, W> {
         self
     }
 
+    /// Write arrays and maps as blocks with a negative item count followed by the size of the block
+    /// in bytes. This allows readers to skip over arrays and maps without decoding them.
+    /// Defaults to `false`.
+    pub fn set_sized_blocks(mut self, sized_blocks: bool) -> Self {
+        self.encode_options.sized_blocks = sized_blocks;
+        self
+    }
+
+    /// Reject records having fields that are not in their schema. By default such fields are ignored.
+    /// Fields missing from a record are always filled from their default value, or rejected if they don't have one.
+    pub fn set_strict_records(mut self, strict_records: bool) -> Self {
+        self.encode_options.strict_records = strict_records;
+        self
+    }
+
+    /// Set a dictionary for the zstd codec, which improves the compression of small blocks.
+    /// If `embed` is true, the dictionary is stored in the datafile header. Otherwise only the id of
+    /// the dictionary is stored and readers look it up in their
+    /// [`CodecRegistry`](struct.CodecRegistry.html), which requires a dictionary trained with zstd.
+    /// Only applies to [`Codec::Zstd`](enum.Codec.html#variant.Zstd), building fails with any other codec.
+    #[cfg(feature = "zstd")]
+    pub fn set_zstd_dictionary(mut self, dictionary: Vec<u8>, embed: bool) -> Self {
+        self.zstd_options.dictionary = Some(Arc::new(dictionary));
+        self.zstd_options.embed_dictionary = embed;
+        self
+    }
+
+    /// Enable long distance matching of the zstd codec, which finds matches further back in large blocks.
+    /// Only applies to [`Codec::Zstd`](enum.Codec.html#variant.Zstd), building fails with any other codec.
+    #[cfg(feature = "zstd")]
+    pub fn set_zstd_long_distance_matching(mut self, enable: bool) -> Self {
+        self.zstd_options.long_distance_matching = enable;
+        self
+    }
+
+    /// Set the window size of the zstd codec as a power of two, from 10 to 31. Readers need as much memory
+    /// as the window to decompress blocks, so it is stored in the datafile header.
+    /// Only applies to [`Codec::Zstd`](enum.Codec.html#variant.Zstd), building fails with any other codec.
+    #[cfg(feature = "zstd")]
+    pub fn set_zstd_window_log(mut self, window_log: u32) -> Self {
+        self.zstd_options.window_log = Some(window_log);
+        self
+    }
+
+    /// Compress blocks on a pool of `threads` worker threads instead of the thread calling `write`.
+    /// Blocks are still written in order and the output is the same as when compressing sequentially.
+    /// This pays off with slow codecs such as xz or zstd at high levels. Defaults to `0`, which
+    /// compresses blocks sequentially.
+    pub fn set_parallel_compression(mut self, threads: usize) -> Self {
+        self.compression_threads = threads;
+        self
+    }
+
+    /// Set the maximum number of blocks being compressed at a time with
+    /// [`set_parallel_compression`](struct.WriterBuilder.html#method.set_parallel_compression),
+    /// which bounds the memory used by the writer. Writes block once this many blocks are in flight.
+    /// Defaults to twice the number of threads.
+    pub fn set_max_blocks_in_flight(mut self, blocks: usize) -> Self {
+        self.max_blocks_in_flight = Some(blocks);
+        self
+    }
+
     /// Builds the `Writer` instance consuming this builder.
-    pub fn build(self) -> AvrowResult<Writer<'a, W>> {
+    #[cfg_attr(not(feature = "zstd"), allow(unused_mut))]
+    pub fn build(mut self) -> AvrowResult<Writer<'a, W>> {
+        #[cfg(feature = "zstd")]
+        {
+            let zstd = self.custom_codec.is_none() && matches!(self.codec, Codec::Zstd { .. });
+            if !zstd && !self.zstd_options.is_default() {
+                return Err(AvrowErr::CodecErr(
+                    "Zstd options are set, but the codec is not zstd".to_string(),
+                ));
+            }
+        }
+        let codec = match &self.custom_codec {
+            Some(name) => BlockCompression::Custom(
+                self.codecs
+                    .get(name)
+                    .ok_or_else(|| AvrowErr::UnsupportedCodec(name.to_string()))?
+                    .clone(),
+            ),
+            None => {
+                self.codec.validate()?;
+                match self.codec {
+                    #[cfg(feature = "zstd")]
+                    Codec::Zstd { level } if !self.zstd_options.is_default() => {
+                        self.zstd_options.write_metadata(&mut self.metadata)?;
+                        BlockCompression::Zstd(level, self.zstd_options)
+                    }
+                    codec => BlockCompression::Builtin(codec),
+                }
+            }
+        };
+        let pool = if self.compression_threads > 0 {
+            let codec = codec.clone();
+            Some(WorkerPool::new(
+                self.compression_threads,
+                move |(count, mut block): (usize, Vec<u8>)| {
+                    let mut compressed = vec![];
+                    codec.encode(&mut block, &mut compressed)?;
+                    Ok((count, compressed))
+                },
+            ))
+        } else {
+            None
+        };
         let mut writer = Writer {
             out_stream: self.datafile.ok_or(AvrowErr::WriterBuildFailed)?,
             schema: self.schema.ok_or(AvrowErr::WriterBuildFailed)?,
             block_stream: Vec::with_capacity(self.flush_interval),
             block_count: 0,
-            codec: self.codec,
+            codec,
             sync_marker: sync_marker(),
             flush_interval: self.flush_interval,
+            encode_options: self.encode_options,
+            pool,
+            max_blocks_in_flight: self
+                .max_blocks_in_flight
+                .unwrap_or(2 * self.compression_threads)
+                .max(1),
         };
         writer.encode_custom_header(self.metadata)?;
         Ok(writer)
@@ -98,6 +241,9 @@ impl<'a, W: Write> Default for WriterBuilder<'a, W> {
     }
 }
 
+// Workers compressing blocks in parallel, which take and yield a block with its datum count.
+type CompressionPool = WorkerPool<(usize, Vec<u8>), AvrowResult<(usize, Vec<u8>)>>;
+
 /// The Writer is the primary interface for writing values to an avro datafile or a byte container (say a `Vec<u8>`).
 /// It takes a reference to the schema for validating the values being written
 /// and an output stream `W` which can be any type
@@ -107,9 +253,12 @@ pub struct Writer<'a, W> {
     schema: &'a Schema,
     block_stream: Vec<u8>,
     block_count: usize,
-    codec: Codec,
+    codec: BlockCompression,
     sync_marker: [u8; 16],
     flush_interval: usize,
+    encode_options: EncodeOptions,
+    pool: Option<CompressionPool>,
+    max_blocks_in_flight: usize,
 }
 
 impl<'a, W: Write> Writer<'a, W> {
@@ -121,9 +270,12 @@ impl<'a, W: Write> Writer<'a, W> {
             schema,
             block_stream: Vec::with_capacity(DEFAULT_FLUSH_INTERVAL),
             block_count: 0,
-            codec: Codec::Null,
+            codec: BlockCompression::Builtin(Codec::Null),
             sync_marker: sync_marker(),
             flush_interval: DEFAULT_FLUSH_INTERVAL,
+            encode_options: EncodeOptions::default(),
+            pool: None,
+            max_blocks_in_flight: 1,
         };
         writer.encode_header()?;
         Ok(writer)
@@ -138,15 +290,21 @@ impl<'a, W: Write> Writer<'a, W> {
     /// * [zstd](https://facebook.github.io/zstd/) compression (`--feature zstd`)
     /// * [bzip](http://www.bzip.org/) compression (`--feature bzip`)
     /// * [xz](https://tukaani.org/xz/) compression (`--features xz`)
+    ///
+    /// All codecs but null and snappy take a compression level. Returns an error if the level is out of range.
     pub fn with_codec(schema: &'a Schema, out_stream: W, codec: Codec) -> AvrowResult<Self> {
+        codec.validate()?;
         let mut writer = Writer {
             out_stream,
             schema,
             block_stream: Vec::with_capacity(DEFAULT_FLUSH_INTERVAL),
             block_count: 0,
-            codec,
+            codec: BlockCompression::Builtin(codec),
             sync_marker: sync_marker(),
             flush_interval: DEFAULT_FLUSH_INTERVAL,
+            encode_options: EncodeOptions::default(),
+            pool: None,
+            max_blocks_in_flight: 1,
         };
         writer.encode_header()?;
         Ok(writer)
@@ -164,18 +322,16 @@ impl<'a, W: Write> Writer<'a, W> {
     /// guarantees that flush will happen and will hand over
     /// the underlying buffer with all data written.
     pub fn write<T: Into<Value>>(&mut self, value: T) -> AvrowResult<()> {
-        let val: Value = value.into();
-        self.schema.validate(&val)?;
-
-        val.encode(
+        encode_value(
+            value.into(),
+            self.schema,
             &mut self.block_stream,
-            &self.schema.variant(),
-            &self.schema.cxt,
+            self.encode_options,
         )?;
         self.block_count += 1;
 
         if self.block_stream.len() >= self.flush_interval {
-            self.flush()?;
+            self.end_block()?;
         }
 
         Ok(())
@@ -194,57 +350,77 @@ impl<'a, W: Write> Writer<'a, W> {
     }
 
     /// Sync/flush any buffered data to the underlying buffer.
+    /// With parallel compression, this waits for all blocks in flight to be compressed and written.
     pub fn flush(&mut self) -> AvrowResult<()> {
+        self.end_block()?;
+        self.write_compressed_blocks(0)
+    }
+
+    // Writes the buffered block, or hands it to the compression workers if compressing in parallel.
+    fn end_block(&mut self) -> AvrowResult<()> {
         // bail if no data is written or it has already been flushed before
         if self.block_count == 0 {
             return Ok(());
         }
-        // encode datum count
-        encode_long(self.block_count as i64, &mut self.out_stream)?;
-        // encode with codec
-        self.codec
-            .encode(&mut self.block_stream, &mut self.out_stream)?;
-        // Write sync marker
-        encode_raw_bytes(&self.sync_marker, &mut self.out_stream)?;
+        if let Some(pool) = &mut self.pool {
+            let block = std::mem::replace(
+                &mut self.block_stream,
+                Vec::with_capacity(self.flush_interval),
+            );
+            pool.submit((self.block_count, block))?;
+            self.block_count = 0;
+            return self.write_compressed_blocks(self.max_blocks_in_flight - 1);
+        }
+        encode_block(
+            &mut self.out_stream,
+            self.block_count,
+            &mut self.block_stream,
+            &self.codec,
+            &self.sync_marker,
+        )?;
         // Reset block buffer
         self.out_stream.flush().map_err(AvrowErr::EncodeFailed)?;
         self.reset_block_buffer();
         Ok(())
     }
 
-    // Used via WriterBuilder
-    fn encode_custom_header(&mut self, mut map: HashMap<String, Value>) -> AvrowResult<()> {
-        self.out_stream
-            .write(MAGIC_BYTES)
-            .map_err(AvrowErr::EncodeFailed)?;
-        map.insert("avro.schema".to_string(), self.schema.as_bytes().into());
-        let codec_str = self.codec.as_ref().as_bytes();
-        map.insert("avro.codec".to_string(), codec_str.into());
-        let meta_schema = &Variant::Map {
-            values: Box::new(Variant::Bytes),
+    // Writes blocks compressed by the workers in order, until at most `in_flight` blocks are left.
+    fn write_compressed_blocks(&mut self, in_flight: usize) -> AvrowResult<()> {
+        let pool = match &mut self.pool {
+            Some(pool) => pool,
+            None => return Ok(()),
         };
-
-        Value::Map(map).encode(&mut self.out_stream, meta_schema, &Registry::new())?;
-        encode_raw_bytes(&self.sync_marker, &mut self.out_stream)?;
+        while pool.in_flight() > in_flight {
+            if let Some(block) = pool.next_in_order()? {
+                let (count, compressed) = block?;
+                encode_long(count as i64, &mut self.out_stream)?;
+                encode_raw_bytes(&compressed, &mut self.out_stream)?;
+                encode_raw_bytes(&self.sync_marker, &mut self.out_stream)?;
+                self.out_stream.flush().map_err(AvrowErr::EncodeFailed)?;
+            }
+        }
         Ok(())
     }
 
-    fn encode_header(&mut self) -> AvrowResult<()> {
-        self.out_stream
-            .write(MAGIC_BYTES)
-            .map_err(AvrowErr::EncodeFailed)?;
-        // encode metadata
-        let mut metamap = Map::with_capacity(2);
-        metamap.insert("avro.schema".to_string(), self.schema.as_bytes().into());
-        let codec_str = self.codec.as_ref().as_bytes();
-        metamap.insert("avro.codec".to_string(), codec_str.into());
-        let meta_schema = &Variant::Map {
-            values: Box::new(Variant::Bytes),
-        };
+    // Used via WriterBuilder
+    fn encode_custom_header(&mut self, map: HashMap<String, Value>) -> AvrowResult<()> {
+        encode_header(
+            &mut self.out_stream,
+            self.schema,
+            &self.codec,
+            map,
+            &self.sync_marker,
+        )
+    }
 
-        Value::Map(metamap).encode(&mut self.out_stream, meta_schema, &Registry::new())?;
-        encode_raw_bytes(&self.sync_marker, &mut self.out_stream)?;
-        Ok(())
+    fn encode_header(&mut self) -> AvrowResult<()> {
+        encode_header(
+            &mut self.out_stream,
+            self.schema,
+            &self.codec,
+            Map::with_capacity(2),
+            &self.sync_marker,
+        )
     }
 
     /// Consumes self and yields the inner `Write` instance.
@@ -255,6 +431,63 @@ impl<'a, W: Write> Writer<'a, W> {
     }
 }
 
+// Validates a value and appends it to the block. The block is left as is if the value is invalid.
+pub(crate) fn encode_value(
+    value: Value,
+    schema: &Schema,
+    block_stream: &mut Vec<u8>,
+    options: EncodeOptions,
+) -> AvrowResult<()> {
+    schema.validate(&value)?;
+
+    let len = block_stream.len();
+    if let Err(e) = value.encode_with(block_stream, schema.variant(), &schema.cxt, options) {
+        // discard the partially encoded value
+        block_stream.truncate(len);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// Writes the datafile header, with the schema and the codec added to the metadata.
+pub(crate) fn encode_header<W: Write>(
+    out_stream: &mut W,
+    schema: &Schema,
+    codec: &BlockCompression,
+    mut metadata: HashMap<String, Value>,
+    sync_marker: &[u8; SYNC_MARKER_SIZE],
+) -> AvrowResult<()> {
+    out_stream
+        .write(MAGIC_BYTES)
+        .map_err(AvrowErr::EncodeFailed)?;
+    metadata.insert("avro.schema".to_string(), schema.as_bytes().into());
+    let codec_str = codec.name().as_bytes();
+    metadata.insert("avro.codec".to_string(), codec_str.into());
+    let meta_schema = &Variant::Map {
+        values: Box::new(Variant::Bytes),
+    };
+
+    Value::Map(metadata).encode(out_stream, meta_schema, &Registry::new())?;
+    encode_raw_bytes(sync_marker, out_stream)?;
+    Ok(())
+}
+
+// Writes a block of `count` values, compressed with the codec and followed by the sync marker.
+pub(crate) fn encode_block<W: Write>(
+    out_stream: &mut W,
+    count: usize,
+    block_stream: &mut [u8],
+    codec: &BlockCompression,
+    sync_marker: &[u8; SYNC_MARKER_SIZE],
+) -> AvrowResult<()> {
+    // encode datum count
+    encode_long(count as i64, out_stream)?;
+    // encode with codec
+    codec.encode(block_stream, out_stream)?;
+    // Write sync marker
+    encode_raw_bytes(sync_marker, out_stream)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{from_value, Codec, Reader, Schema, Writer, WriterBuilder};
@@ -314,4 +547,215 @@ mod tests {
         let reader = Reader::with_schema(_v.as_slice(), &schema).unwrap();
         assert!(reader.meta().contains_key("hello"));
     }
+
+    #[test]
+    fn sized_blocks_can_be_skipped() {
+        let schema = Schema::from_str(
+            r##"{"type": "record", "name": "Tagged", "fields": [
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+                {"name": "id", "type": "int"}
+            ]}"##,
+        )
+        .unwrap();
+        let reader_schema = Schema::from_str(
+            r##"{"type": "record", "name": "Tagged", "fields": [{"name": "id", "type": "int"}]}"##,
+        )
+        .unwrap();
+        let mut record = crate::Record::new("Tagged");
+        record.insert("tags", vec!["a", "bc"]).unwrap();
+        record.insert("id", 42).unwrap();
+        let mut writer = WriterBuilder::new()
+            .set_schema(&schema)
+            .set_datafile(vec![])
+            .set_sized_blocks(true)
+            .build()
+            .unwrap();
+        writer.write(crate::Value::Record(record.clone())).unwrap();
+        let buf = writer.into_inner().unwrap();
+
+        let mut reader = Reader::new(buf.as_slice()).unwrap();
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            crate::Value::Record(record)
+        );
+        let mut expected = crate::Record::new("Tagged");
+        expected.insert("id", 42).unwrap();
+        let mut reader = Reader::with_schema(buf.as_slice(), &reader_schema).unwrap();
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            crate::Value::Record(expected)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_dictionary_and_options() {
+        use crate::{AvrowErr, CodecRegistry, ReaderBuilder};
+
+        let schema = Schema::from_str(r##""string""##).unwrap();
+        let samples: Vec<Vec<u8>> = (0..1000)
+            .map(|i| format!("{{\"user\": {}, \"event\": \"login\"}}", i).into_bytes())
+            .collect();
+        let dictionary = zstdd::dict::from_samples(&samples, 4096).unwrap();
+        let write = |builder: WriterBuilder<'_, Vec<u8>>| {
+            let mut writer = builder
+                .set_schema(&schema)
+                .set_datafile(vec![])
+                .set_codec(Codec::Zstd { level: 3 })
+                .set_flush_interval(64)
+                .build()
+                .unwrap();
+            for sample in &samples[..100] {
+                writer
+                    .write(String::from_utf8(sample.clone()).unwrap())
+                    .unwrap();
+            }
+            writer.into_inner().unwrap()
+        };
+        let read_all = |reader: Reader<'_, &[u8]>| {
+            let values: Vec<_> = reader.map(|v| v.unwrap()).collect();
+            assert_eq!(values.len(), 100);
+            assert_eq!(
+                values[99],
+                crate::Value::Str(String::from_utf8(samples[99].clone()).unwrap())
+            );
+        };
+
+        // embedded in the header
+        let buf = write(WriterBuilder::new().set_zstd_dictionary(dictionary.clone(), true));
+        let reader = Reader::new(buf.as_slice()).unwrap();
+        assert_eq!(reader.meta()["avro.zstd.dictionary"], dictionary);
+        read_all(reader);
+
+        // referred to by id and looked up in the registry
+        let buf = write(WriterBuilder::new().set_zstd_dictionary(dictionary.clone(), false));
+        assert!(matches!(
+            Reader::new(buf.as_slice()).err(),
+            Some(AvrowErr::ZstdDictionaryNotFound(_))
+        ));
+        let mut codecs = CodecRegistry::new();
+        let id = codecs.register_zstd_dictionary(dictionary).unwrap();
+        let reader = ReaderBuilder::new()
+            .set_source(buf.as_slice())
+            .set_codec_registry(codecs)
+            .build()
+            .unwrap();
+        assert_eq!(
+            reader.meta()["avro.zstd.dictionary.id"],
+            id.to_string().as_bytes()
+        );
+        read_all(reader);
+
+        // a raw content dictionary has no id
+        let raw = b"{\"user\": , \"event\": \"login\"}".to_vec();
+        assert!(WriterBuilder::new()
+            .set_schema(&schema)
+            .set_datafile(vec![])
+            .set_codec(Codec::Zstd { level: 3 })
+            .set_zstd_dictionary(raw.clone(), false)
+            .build()
+            .is_err());
+        read_all(
+            Reader::new(write(WriterBuilder::new().set_zstd_dictionary(raw, true)).as_slice())
+                .unwrap(),
+        );
+
+        let buf = write(
+            WriterBuilder::new()
+                .set_zstd_long_distance_matching(true)
+                .set_zstd_window_log(20),
+        );
+        let reader = Reader::new(buf.as_slice()).unwrap();
+        assert_eq!(reader.meta()["avro.zstd.window_log"], b"20");
+        read_all(reader);
+        assert!(WriterBuilder::new()
+            .set_schema(&schema)
+            .set_datafile(vec![])
+            .set_codec(Codec::Zstd { level: 3 })
+            .set_zstd_window_log(32)
+            .build()
+            .is_err());
+
+        // zstd options with another codec
+        assert!(matches!(
+            WriterBuilder::new()
+                .set_schema(&schema)
+                .set_datafile(vec![])
+                .set_zstd_long_distance_matching(true)
+                .build()
+                .err(),
+            Some(AvrowErr::CodecErr(_))
+        ));
+        assert!(matches!(
+            WriterBuilder::new()
+                .set_schema(&schema)
+                .set_datafile(vec![])
+                .set_codec(Codec::Zstd { level: 3 })
+                .set_custom_codec("xor")
+                .set_zstd_window_log(20)
+                .build()
+                .err(),
+            Some(AvrowErr::CodecErr(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "deflate")]
+    fn parallel_compression_matches_sequential() {
+        let schema = Schema::from_str(r##"{"type": "array", "items": "long"}"##).unwrap();
+        let write = |threads: usize, in_flight: usize| {
+            let mut writer = WriterBuilder::new()
+                .set_schema(&schema)
+                .set_datafile(vec![])
+                .set_codec(Codec::Deflate { level: 9 })
+                .set_flush_interval(256)
+                .set_parallel_compression(threads)
+                .set_max_blocks_in_flight(in_flight)
+                .build()
+                .unwrap();
+            for i in 0..500 {
+                writer.serialize(vec![i, i * 2, i % 7]).unwrap();
+                if i == 250 {
+                    writer.flush().unwrap();
+                }
+            }
+            let marker = writer.sync_marker;
+            let buf = writer.into_inner().unwrap();
+            // header metadata and sync markers differ between writers, so only the blocks
+            // between the markers are compared
+            let mut blocks = vec![];
+            let mut start = 0;
+            for idx in 0..=buf.len() - marker.len() {
+                if buf[idx..idx + marker.len()] == marker {
+                    blocks.push(buf[start..idx].to_vec());
+                    start = idx + marker.len();
+                }
+            }
+            blocks.remove(0);
+            blocks
+        };
+        let sequential = write(0, 1);
+        assert_eq!(sequential.len(), 14);
+        assert!(write(4, 1) == sequential);
+        assert!(write(4, 3) == sequential);
+        assert!(write(2, 16) == sequential);
+
+        let mut writer = WriterBuilder::new()
+            .set_schema(&schema)
+            .set_datafile(vec![])
+            .set_codec(Codec::Deflate { level: 9 })
+            .set_flush_interval(256)
+            .set_parallel_compression(3)
+            .build()
+            .unwrap();
+        for i in 0..500 {
+            writer.serialize(vec![i]).unwrap();
+        }
+        let buf = writer.into_inner().unwrap();
+        let values: Vec<Vec<i64>> = Reader::new(buf.as_slice())
+            .unwrap()
+            .map(|v| from_value(&v).unwrap())
+            .collect();
+        assert_eq!(values, (0..500).map(|i| vec![i]).collect::<Vec<_>>());
+    }
 }
@@ -0,0 +1,123 @@
+#![cfg(feature = "async")]
+
+use avrow::{from_value, AsyncReader, AsyncWriter, Codec, Reader, Record, Schema, Value, Writer};
+use futures::executor::block_on;
+use futures::StreamExt;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+// A source that yields a few bytes at a time and is not ready every other poll.
+struct Trickle<'a> {
+    data: &'a [u8],
+    ready: bool,
+}
+
+impl<'a> AsyncRead for Trickle<'a> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.ready = !self.ready;
+        if !self.ready {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let len = self.data.len().min(buf.remaining()).min(7);
+        buf.put_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn schema() -> Schema {
+    Schema::from_str(
+        r##"{"type": "record", "name": "Reading", "fields": [
+            {"name": "sensor", "type": "string"},
+            {"name": "value", "type": "double"}
+        ]}"##,
+    )
+    .unwrap()
+}
+
+fn reading(idx: usize) -> Value {
+    let mut reading = Record::new("Reading");
+    reading
+        .insert("sensor", format!("sensor-{}", idx % 3))
+        .unwrap();
+    reading.insert("value", idx as f64 / 2.0).unwrap();
+    Value::Record(reading)
+}
+
+#[test]
+fn async_writer_output_is_readable_by_reader() {
+    let schema = schema();
+    let buf = block_on(async {
+        let mut writer = AsyncWriter::with_codec(&schema, vec![], Codec::Null)
+            .await
+            .unwrap();
+        writer.set_flush_interval(100);
+        for idx in 0..100 {
+            writer.write(reading(idx)).await.unwrap();
+        }
+        assert!(writer.write(Value::Int(1)).await.is_err());
+        writer.into_inner().await.unwrap()
+    });
+
+    let values: Vec<_> = Reader::new(buf.as_slice()).unwrap().collect();
+    assert_eq!(values.len(), 100);
+    assert_eq!(values[42].as_ref().unwrap(), &reading(42));
+}
+
+#[test]
+fn async_reader_reads_incrementally() {
+    let schema = schema();
+    let mut writer = Writer::new(&schema, vec![]).unwrap();
+    for idx in 0..50 {
+        writer.write(reading(idx)).unwrap();
+        if idx % 10 == 0 {
+            writer.flush().unwrap();
+        }
+    }
+    let buf = writer.into_inner().unwrap();
+
+    let values: Vec<_> = block_on(async {
+        let source = Trickle {
+            data: &buf,
+            ready: false,
+        };
+        let reader = AsyncReader::new(source).await.unwrap();
+        assert!(reader.meta().contains_key("avro.schema"));
+        reader.collect().await
+    });
+    assert_eq!(values.len(), 50);
+    for (idx, value) in values.iter().enumerate() {
+        assert_eq!(value.as_ref().unwrap(), &reading(idx));
+    }
+
+    // a truncated datafile fails at the last block
+    let values: Vec<_> = block_on(async {
+        let reader = AsyncReader::new(&buf[..buf.len() - 4]).await.unwrap();
+        reader.collect().await
+    });
+    assert!(values.last().unwrap().is_err());
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "Reading", "fields": [{"name": "value", "type": "double"}]}"##,
+    )
+    .unwrap();
+    let value = block_on(async {
+        let mut reader = AsyncReader::with_schema(buf.as_slice(), &reader_schema)
+            .await
+            .unwrap();
+        reader.next().await.unwrap()
+    });
+    #[derive(serde_derive::Deserialize)]
+    struct Sample {
+        value: f64,
+    }
+    let sample: Sample = from_value(&value).unwrap();
+    assert_eq!(sample.value, 0.0);
+}
@@ -4,9 +4,13 @@ extern crate serde_derive;
 mod common;
 
 use crate::common::{writer_from_schema, MockSchema};
-use avrow::{from_value, Codec, Reader, Schema, Value};
+use avrow::{
+    from_value, BlockCodec, Codec, CodecRegistry, Decimal, Reader, ReaderBuilder, Record, Schema,
+    UnionBranch, Value, Writer, WriterBuilder,
+};
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use common::Primitive;
 use serde_derive::{Deserialize, Serialize};
@@ -34,15 +38,15 @@ static PRIMITIVES: [Primitive; 8] = [
 #[cfg(feature = "codec")]
 const CODECS: [Codec; 6] = [
     Codec::Null,
-    Codec::Deflate,
+    Codec::Deflate { level: 6 },
     Codec::Snappy,
-    Codec::Zstd,
-    Codec::Bzip2,
-    Codec::Xz,
+    Codec::Zstd { level: 3 },
+    Codec::Bzip2 { level: 5 },
+    Codec::Xz { level: 6 },
 ];
 
 // #[cfg(feature = "bzip2")]
-// const CODECS: [Codec; 1] = [Codec::Bzip2];
+// const CODECS: [Codec; 1] = [Codec::Bzip2 { level: 5 }];
 
 #[test]
 #[cfg(feature = "codec")]
@@ -301,6 +305,343 @@ fn union_read_write() {
     }
 }
 
+#[test]
+#[cfg(feature = "codec")]
+fn codec_levels_read_write() {
+    let schema = Schema::from_str(r##"{"type": "array", "items": "string"}"##).unwrap();
+    let value: Vec<String> = (0..100).map(|i| format!("value {}", i % 7)).collect();
+    let mut codecs = vec![Codec::Snappy];
+    for level in 0..=9 {
+        codecs.push(Codec::Deflate { level });
+        codecs.push(Codec::Xz { level });
+    }
+    for level in 1..=9 {
+        codecs.push(Codec::Bzip2 { level });
+    }
+    for level in &[-5, 0, 1, 3, 19, 22] {
+        codecs.push(Codec::Zstd { level: *level });
+    }
+    for codec in codecs {
+        let mut writer = WriterBuilder::new()
+            .set_schema(&schema)
+            .set_datafile(vec![])
+            .set_codec(codec)
+            .set_flush_interval(1024)
+            .build()
+            .unwrap();
+        for _ in 0..20 {
+            writer.serialize(&value).unwrap();
+        }
+        let buf = writer.into_inner().unwrap();
+        let plain_len = 20 * value.iter().map(|v| v.len() + 1).sum::<usize>();
+        if codec != (Codec::Deflate { level: 0 }) {
+            assert!(buf.len() < plain_len, "{:?} did not compress", codec);
+        }
+
+        let reader = Reader::new(buf.as_slice()).unwrap();
+        let mut count = 0;
+        for v in reader {
+            let read: Vec<String> = from_value(&v).unwrap();
+            assert_eq!(read, value, "{:?}", codec);
+            count += 1;
+        }
+        assert_eq!(count, 20);
+    }
+    assert!(Writer::with_codec(&schema, vec![], Codec::Bzip2 { level: 0 }).is_err());
+    assert!(Writer::with_codec(&schema, vec![], Codec::Deflate { level: 10 }).is_err());
+}
+
+// A toy encryption codec, xor-ing every byte with a key.
+#[derive(Debug)]
+struct XorCodec(u8);
+
+impl BlockCodec for XorCodec {
+    fn name(&self) -> &str {
+        "xor"
+    }
+
+    fn compress(&self, block: &[u8]) -> Result<Vec<u8>, avrow::AvrowErr> {
+        Ok(block.iter().map(|b| b ^ self.0).collect())
+    }
+
+    fn decompress(
+        &self,
+        compressed: &[u8],
+        uncompressed: &mut Vec<u8>,
+    ) -> Result<(), avrow::AvrowErr> {
+        uncompressed.extend(compressed.iter().map(|b| b ^ self.0));
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_codec_read_write() {
+    let schema = Schema::from_str(r##""string""##).unwrap();
+    let mut codecs = CodecRegistry::new();
+    codecs.register(XorCodec(42));
+    let mut writer = WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_codec_registry(codecs.clone())
+        .set_custom_codec("xor")
+        .set_flush_interval(16)
+        .build()
+        .unwrap();
+    for i in 0..10 {
+        writer.serialize(format!("secret {}", i)).unwrap();
+    }
+    let buf = writer.into_inner().unwrap();
+    assert!(!buf.windows(6).any(|w| w == b"secret"));
+
+    let reader = ReaderBuilder::new()
+        .set_source(buf.as_slice())
+        .set_codec_registry(codecs)
+        .build()
+        .unwrap();
+    assert_eq!(reader.meta()["avro.codec"], b"xor");
+    let values: Vec<_> = reader.map(|v| v.unwrap()).collect();
+    assert_eq!(values[9], Value::Str("secret 9".to_string()));
+
+    assert!(matches!(
+        Reader::new(buf.as_slice()).err(),
+        Some(avrow::AvrowErr::UnsupportedCodec(_))
+    ));
+    assert!(WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_custom_codec("lz4")
+        .build()
+        .is_err());
+}
+
+// A codec that panics once while compressing or decompressing a block, leaving the other
+// workers running.
+#[derive(Debug)]
+struct PanickingCodec {
+    on_compress: bool,
+    panicked: AtomicBool,
+}
+
+impl PanickingCodec {
+    fn new(on_compress: bool) -> Self {
+        PanickingCodec {
+            on_compress,
+            panicked: AtomicBool::new(false),
+        }
+    }
+
+    fn panic_once(&self, message: &str) {
+        if !self.panicked.swap(true, Ordering::SeqCst) {
+            panic!("{}", message);
+        }
+    }
+}
+
+impl BlockCodec for PanickingCodec {
+    fn name(&self) -> &str {
+        "panicking"
+    }
+
+    fn compress(&self, block: &[u8]) -> Result<Vec<u8>, avrow::AvrowErr> {
+        if self.on_compress {
+            self.panic_once("compress failed");
+        }
+        Ok(block.to_vec())
+    }
+
+    fn decompress(
+        &self,
+        compressed: &[u8],
+        uncompressed: &mut Vec<u8>,
+    ) -> Result<(), avrow::AvrowErr> {
+        if !self.on_compress {
+            self.panic_once("decompress failed");
+        }
+        uncompressed.extend_from_slice(compressed);
+        Ok(())
+    }
+}
+
+#[test]
+fn parallel_compression_panicking_codec() {
+    let schema = Schema::from_str(r##""long""##).unwrap();
+    let mut codecs = CodecRegistry::new();
+    codecs.register(PanickingCodec::new(true));
+    let mut writer = WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_codec_registry(codecs)
+        .set_custom_codec("panicking")
+        .set_flush_interval(16)
+        .set_parallel_compression(3)
+        .build()
+        .unwrap();
+    let written = (0..100i64).try_for_each(|i| writer.serialize(i));
+    assert!(matches!(
+        written.and_then(|_| writer.flush()),
+        Err(avrow::AvrowErr::WorkerStopped)
+    ));
+}
+
+#[test]
+fn parallel_decoding_panicking_codec() {
+    let schema = Schema::from_str(r##""long""##).unwrap();
+    let mut codecs = CodecRegistry::new();
+    codecs.register(PanickingCodec::new(false));
+    let mut writer = WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_codec_registry(codecs)
+        .set_custom_codec("panicking")
+        .set_flush_interval(16)
+        .build()
+        .unwrap();
+    for i in 0..100i64 {
+        writer.serialize(i).unwrap();
+    }
+    let buf = writer.into_inner().unwrap();
+
+    for &ordered in &[true, false] {
+        let mut codecs = CodecRegistry::new();
+        codecs.register(PanickingCodec::new(false));
+        let values: Vec<_> = ReaderBuilder::new()
+            .set_source(buf.as_slice())
+            .set_codec_registry(codecs)
+            .set_parallel_decoding(3)
+            .set_ordered(ordered)
+            .build()
+            .unwrap()
+            .collect();
+        assert!(values
+            .iter()
+            .any(|v| matches!(v, Err(avrow::AvrowErr::WorkerStopped))));
+        assert!(values.iter().filter(|v| v.is_ok()).count() < 100);
+    }
+}
+
+#[test]
+fn parallel_decoding_read() {
+    let schema = Schema::from_str(
+        r##"{"type": "record", "name": "Event", "fields": [{"name": "id", "type": "int"}]}"##,
+    )
+    .unwrap();
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "Event", "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "source", "type": "string", "default": "web"}
+        ]}"##,
+    )
+    .unwrap();
+    let mut writer = WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_flush_interval(64)
+        .build()
+        .unwrap();
+    for id in 0..1000 {
+        let mut event = Record::new("Event");
+        event.insert("id", id).unwrap();
+        writer.write(Value::Record(event)).unwrap();
+    }
+    let buf = writer.into_inner().unwrap();
+    #[derive(Deserialize)]
+    struct Event {
+        id: i64,
+        source: String,
+    }
+    let ids = |reader: Reader<'_, &[u8]>| -> Vec<i64> {
+        reader
+            .map(|v| {
+                let event: Event = from_value(&v).unwrap();
+                assert_eq!(event.source, "web");
+                event.id
+            })
+            .collect()
+    };
+
+    let ordered = ReaderBuilder::new()
+        .set_source(buf.as_slice())
+        .set_schema(&reader_schema)
+        .set_parallel_decoding(4)
+        .set_max_blocks_in_flight(3)
+        .build()
+        .unwrap();
+    assert_eq!(ids(ordered), (0..1000).collect::<Vec<_>>());
+
+    let unordered = ReaderBuilder::new()
+        .set_source(buf.as_slice())
+        .set_schema(&reader_schema)
+        .set_parallel_decoding(4)
+        .set_ordered(false)
+        .build()
+        .unwrap();
+    let mut read = ids(unordered);
+    read.sort();
+    assert_eq!(read, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn union_branch_selection() {
+    let schema = Schema::from_str(
+        r##"["null", "long", "string", "bytes",
+            {"type": "record", "name": "Cat", "fields": [{"name": "lives", "type": "int"}]},
+            {"type": "record", "name": "Dog", "fields": [{"name": "lives", "type": "int"}]},
+            {"type": "enum", "name": "Color", "symbols": ["RED"]},
+            {"type": "enum", "name": "Size", "symbols": ["SMALL"]}
+        ]"##,
+    )
+    .unwrap();
+    let mut dog = Record::new("Dog");
+    dog.insert("lives", 1).unwrap();
+    let values = vec![
+        // promoted to long
+        Value::Int(5),
+        Value::Record(dog),
+        Value::Enum("SMALL".to_string()),
+        Value::Union {
+            branch: Some(UnionBranch::Name("bytes".to_string())),
+            value: Box::new(Value::Str("a".to_string())),
+        },
+        Value::Union {
+            branch: Some(UnionBranch::Index(2)),
+            value: Box::new(Value::Bytes(b"b".to_vec())),
+        },
+        Value::Union {
+            branch: None,
+            value: Box::new(Value::Str("c".to_string())),
+        },
+    ];
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    for v in &values {
+        writer.write(v.clone()).unwrap();
+    }
+    let missing = Value::Union {
+        branch: Some(UnionBranch::Index(8)),
+        value: Box::new(Value::Null),
+    };
+    assert!(writer.write(missing).is_err());
+    let nested = Value::Union {
+        branch: None,
+        value: Box::new(Value::Union {
+            branch: Some(UnionBranch::Index(0)),
+            value: Box::new(Value::Null),
+        }),
+    };
+    assert!(writer.write(nested).is_err());
+    let buf = writer.into_inner().unwrap();
+
+    let read: Vec<_> = Reader::new(buf.as_slice())
+        .unwrap()
+        .map(|v| v.unwrap())
+        .collect();
+    assert_eq!(read[0], Value::Long(5));
+    assert_eq!(read[1], values[1]);
+    assert_eq!(read[2], values[2]);
+    assert_eq!(read[3], Value::Bytes(b"a".to_vec()));
+    assert_eq!(read[4], Value::Str("b".to_string()));
+    assert_eq!(read[5], Value::Str("c".to_string()));
+}
+
 #[test]
 #[cfg(feature = "codec")]
 fn fixed_read_write() {
@@ -335,7 +676,7 @@ fn fixed_read_write() {
 #[cfg(feature = "codec")]
 fn bytes_read_write() {
     let schema = Schema::from_str(r##"{"type": "bytes"}"##).unwrap();
-    let mut writer = writer_from_schema(&schema, avrow::Codec::Deflate);
+    let mut writer = writer_from_schema(&schema, avrow::Codec::Deflate { level: 6 });
     let data = vec![0u8, 1u8, 2u8, 3u8, 4u8, 5u8];
     writer.serialize(&data).unwrap();
 
@@ -357,6 +698,55 @@ fn write_invalid_union_data_fails() {
     writer.serialize("string").unwrap();
 }
 
+#[test]
+fn record_fields_written_in_schema_order() {
+    let schema = Schema::from_str(
+        r##"{"type": "record", "name": "User", "fields": [
+            {"name": "id", "type": "int"},
+            {"name": "name", "type": "string"},
+            {"name": "active", "type": "boolean", "default": true}
+        ]}"##,
+    )
+    .unwrap();
+    let mut record = Record::new("User");
+    record.insert("name", "alice").unwrap();
+    record.insert("id", 1).unwrap();
+    record.insert("nickname", "al").unwrap();
+
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.write(Value::Record(record.clone())).unwrap();
+    let mut missing = Record::new("User");
+    missing.insert("name", "bob").unwrap();
+    let err = writer.write(Value::Record(missing)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Value does not match the schema: /id: expected int, found no value and no default"
+    );
+    let buf = writer.into_inner().unwrap();
+
+    let mut expected = Record::new("User");
+    expected.insert("id", 1).unwrap();
+    expected.insert("name", "alice").unwrap();
+    expected.insert("active", true).unwrap();
+    let values: Vec<_> = Reader::new(buf.as_slice())
+        .unwrap()
+        .map(|v| v.unwrap())
+        .collect();
+    assert_eq!(values, vec![Value::Record(expected)]);
+
+    let mut writer = WriterBuilder::new()
+        .set_schema(&schema)
+        .set_datafile(vec![])
+        .set_strict_records(true)
+        .build()
+        .unwrap();
+    let err = writer.write(Value::Record(record)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Record `User` has a field `nickname` that is not in its schema"
+    );
+}
+
 #[test]
 #[cfg(feature = "snappy")]
 fn read_deflate_reuse() {
@@ -470,3 +860,394 @@ fn parses_field_record_defined_within_union() {
         assert_eq!(a, reference);
     }
 }
+
+#[test]
+fn decimal_read_write() {
+    for schema in &[
+        r##"{"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}"##,
+        r##"{"type": "fixed", "name": "money", "size": 8, "logicalType": "decimal", "precision": 10, "scale": 2}"##,
+    ] {
+        let schema = Schema::from_str(schema).unwrap();
+        let mut writer = writer_from_schema(&schema, Codec::Null);
+        writer.write(Decimal::new(-123456, 2)).unwrap();
+        // values are rescaled to the scale of the schema
+        writer.write(Decimal::from_str("7.5").unwrap()).unwrap();
+        assert!(writer.write(Decimal::new(1, 3)).is_err());
+        assert!(writer.write(Decimal::new(12345678901i64, 2)).is_err());
+        let buf = writer.into_inner().unwrap();
+
+        let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+        let values: Vec<Value> = reader.map(|v| v.unwrap()).collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::Decimal(Decimal::new(-123456, 2)),
+                Value::Decimal(Decimal::new(750, 2))
+            ]
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "decimal")]
+fn decimal_serde_read_write() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Payment {
+        id: i64,
+        amount: rust_decimal::Decimal,
+    }
+
+    let schema = Schema::from_str(
+        r##"
+        {
+            "type": "record",
+            "name": "Payment",
+            "fields": [
+                {"name": "id", "type": "long"},
+                {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 12, "scale": 2}}
+            ]
+        }
+        "##,
+    )
+    .unwrap();
+
+    let payment = Payment {
+        id: 1,
+        amount: rust_decimal::Decimal::new(-1999, 2),
+    };
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.serialize(&payment).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    for i in reader {
+        let p: Payment = from_value(&i).unwrap();
+        assert_eq!(p, payment);
+    }
+}
+
+#[test]
+fn temporal_read_write() {
+    let schema =
+        Schema::from_str(r##"{"type": "long", "logicalType": "timestamp-micros"}"##).unwrap();
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer
+        .write(Value::TimestampMicros(1_600_000_000_000_000))
+        .unwrap();
+    // the underlying long can be written as is
+    writer.write(42i64).unwrap();
+    // a timestamp of another precision is converted to the precision of the schema
+    writer.write(Value::TimestampMillis(1)).unwrap();
+    assert!(writer.write(Value::LocalTimestampMicros(1)).is_err());
+    assert!(writer.write(Value::TimestampMillis(i64::MAX)).is_err());
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    let values: Vec<Value> = reader.map(|v| v.unwrap()).collect();
+    assert_eq!(
+        values[0].as_timestamp_micros().unwrap(),
+        &1_600_000_000_000_000
+    );
+    assert_eq!(values[1], Value::TimestampMicros(42));
+    assert_eq!(values[2], Value::TimestampMicros(1000));
+
+    // a plain int written by the writer is read as the underlying int by a date reader
+    let schema = Schema::from_str(r##""int""##).unwrap();
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.write(18262).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(r##"{"type": "int", "logicalType": "date"}"##).unwrap();
+    let mut reader = Reader::with_schema(buf.as_slice(), &reader_schema).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), Value::Int(18262));
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn temporal_serde_read_write() {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Event {
+        day: NaiveDate,
+        time: NaiveTime,
+        at: DateTime<Utc>,
+        at_millis: DateTime<Utc>,
+        local: NaiveDateTime,
+    }
+
+    let schema = Schema::from_str(
+        r##"
+        {
+            "type": "record",
+            "name": "Event",
+            "fields": [
+                {"name": "day", "type": {"type": "int", "logicalType": "date"}},
+                {"name": "time", "type": {"type": "int", "logicalType": "time-millis"}},
+                {"name": "at", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "at_millis", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                {"name": "local", "type": {"type": "long", "logicalType": "local-timestamp-micros"}}
+            ]
+        }
+        "##,
+    )
+    .unwrap();
+
+    let at = Utc.with_ymd_and_hms(2020, 9, 13, 12, 26, 40).unwrap();
+    let event = Event {
+        day: NaiveDate::from_ymd_opt(2020, 9, 13).unwrap(),
+        time: NaiveTime::from_hms_milli_opt(12, 26, 40, 123).unwrap(),
+        at: at + chrono::Duration::microseconds(456),
+        at_millis: at,
+        local: NaiveDate::from_ymd_opt(1960, 1, 1)
+            .unwrap()
+            .and_hms_micro_opt(0, 0, 0, 1)
+            .unwrap(),
+    };
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.serialize(&event).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    for i in reader {
+        let e: Event = from_value(&i).unwrap();
+        assert_eq!(e, event);
+    }
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn temporal_values_read_write_with_every_precision() {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Times {
+        time: NaiveTime,
+        at: DateTime<Utc>,
+        local: NaiveDateTime,
+    }
+
+    let local = NaiveDate::from_ymd_opt(1960, 1, 1)
+        .unwrap()
+        .and_hms_milli_opt(12, 26, 40, 123)
+        .unwrap();
+    let times = Times {
+        time: local.time(),
+        at: Utc.from_utc_datetime(&local),
+        local,
+    };
+    for (time, precision) in &[("time-millis", "millis"), ("time-micros", "micros")] {
+        let schema = Schema::from_str(&format!(
+            r##"
+            {{
+                "type": "record",
+                "name": "Times",
+                "fields": [
+                    {{"name": "time", "type": {{"type": "{}", "logicalType": "{}"}}}},
+                    {{"name": "at", "type": {{"type": "long", "logicalType": "timestamp-{}"}}}},
+                    {{"name": "local", "type": {{"type": "long", "logicalType": "local-timestamp-{}"}}}}
+                ]
+            }}
+            "##,
+            if *time == "time-millis" { "int" } else { "long" },
+            time,
+            precision,
+            precision
+        ))
+        .unwrap();
+
+        let mut writer = writer_from_schema(&schema, Codec::Null);
+        writer.serialize(&times).unwrap();
+        let mut record = Record::new("Times");
+        record.insert("time", times.time).unwrap();
+        record.insert("at", times.at).unwrap();
+        record.insert("local", times.local).unwrap();
+        writer.write(Value::Record(record)).unwrap();
+        let buf = writer.into_inner().unwrap();
+
+        let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+        let mut count = 0;
+        for i in reader {
+            let t: Times = from_value(&i).unwrap();
+            assert_eq!(t, times);
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+}
+
+#[test]
+fn uuid_read_write() {
+    let id = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+    for schema in &[
+        r##"{"type": "string", "logicalType": "uuid"}"##,
+        r##"{"type": "fixed", "name": "id", "size": 16, "logicalType": "uuid"}"##,
+    ] {
+        let schema = Schema::from_str(schema).unwrap();
+        let mut writer = writer_from_schema(&schema, Codec::Null);
+        writer.write(Value::Uuid(id.to_string())).unwrap();
+        writer.serialize(id).unwrap();
+        assert!(writer.write(Value::Uuid("f81d4fae".to_string())).is_err());
+        assert!(writer.serialize("not-a-uuid").is_err());
+        let buf = writer.into_inner().unwrap();
+
+        let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+        for i in reader {
+            assert_eq!(i.unwrap().as_uuid().unwrap(), id);
+        }
+    }
+}
+
+#[test]
+fn duration_read_write() {
+    let schema = Schema::from_str(
+        r##"{"type": "fixed", "name": "span", "size": 12, "logicalType": "duration"}"##,
+    )
+    .unwrap();
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer
+        .write(Value::Duration {
+            months: 1,
+            days: 2,
+            millis: 3,
+        })
+        .unwrap();
+    writer.write(Value::Fixed(vec![0; 12])).unwrap();
+    assert!(writer.write(Value::Fixed(vec![0; 8])).is_err());
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    let values: Vec<Value> = reader.map(|v| v.unwrap()).collect();
+    assert_eq!(values[0].as_duration().unwrap(), (1, 2, 3));
+    assert_eq!(values[1].as_duration().unwrap(), (0, 0, 0));
+}
+
+#[test]
+#[cfg(feature = "uuid")]
+fn uuid_serde_read_write() {
+    use std::convert::TryFrom;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct User {
+        id: uuid::Uuid,
+        name: String,
+    }
+
+    let schema = Schema::from_str(
+        r##"
+        {
+            "type": "record",
+            "name": "User",
+            "fields": [
+                {"name": "id", "type": {"type": "fixed", "name": "id", "size": 16, "logicalType": "uuid"}},
+                {"name": "name", "type": "string"}
+            ]
+        }
+        "##,
+    )
+    .unwrap();
+
+    let user = User {
+        id: uuid::Uuid::parse_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap(),
+        name: "avrow".to_string(),
+    };
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.serialize(&user).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    for i in reader {
+        let u: User = from_value(&i).unwrap();
+        assert_eq!(u, user);
+    }
+
+    let value = Value::from(user.id);
+    assert_eq!(uuid::Uuid::try_from(value).unwrap(), user.id);
+}
+
+// Represents a geo point as a "lat,lon" string, stored as a record of two doubles.
+#[derive(Debug)]
+struct GeoPoint;
+
+impl avrow::LogicalType for GeoPoint {
+    fn name(&self) -> &str {
+        "geo-point"
+    }
+
+    fn validate_schema(
+        &self,
+        schema: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), avrow::AvrowErr> {
+        match schema.get("type") {
+            Some(serde_json::Value::String(s)) if s == "record" => Ok(()),
+            _ => Err(avrow::AvrowErr::LogicalTypeErr(
+                "geo-point must annotate a record".to_string(),
+            )),
+        }
+    }
+
+    fn to_underlying(&self, value: &Value) -> Result<Value, avrow::AvrowErr> {
+        match value {
+            Value::Str(s) => {
+                let err = || avrow::AvrowErr::LogicalTypeErr(format!("invalid geo point {}", s));
+                let mut parts = s.split(',').map(|p| p.parse::<f64>());
+                let lat = parts.next().and_then(|p| p.ok()).ok_or_else(err)?;
+                let lon = parts.next().and_then(|p| p.ok()).ok_or_else(err)?;
+                let mut rec = avrow::Record::new("GeoPoint");
+                rec.insert("lat", lat)?;
+                rec.insert("lon", lon)?;
+                Ok(Value::Record(rec))
+            }
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn to_logical(&self, value: Value) -> Result<Value, avrow::AvrowErr> {
+        #[derive(Deserialize)]
+        struct Point {
+            lat: f64,
+            lon: f64,
+        }
+        let p: Point = from_value(&Ok(value))?;
+        Ok(Value::Str(format!("{},{}", p.lat, p.lon)))
+    }
+}
+
+#[test]
+fn custom_logical_type_read_write() {
+    let schema_str = r##"
+        {
+            "type": "record",
+            "name": "GeoPoint",
+            "logicalType": "geo-point",
+            "fields": [
+                {"name": "lat", "type": "double"},
+                {"name": "lon", "type": "double"}
+            ]
+        }
+        "##;
+    let mut schema = Schema::from_str(schema_str).unwrap();
+    schema.register_logical_type(GeoPoint).unwrap();
+
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.write("52.5,13.25").unwrap();
+    assert!(writer.write("52.5").is_err());
+    let buf = writer.into_inner().unwrap();
+
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    for i in reader {
+        assert_eq!(i.unwrap(), Value::Str("52.5,13.25".to_string()));
+    }
+
+    // without the registered logical type, the underlying record is read
+    let schema = Schema::from_str(schema_str).unwrap();
+    let reader = Reader::with_schema(buf.as_slice(), &schema).unwrap();
+    for i in reader {
+        assert!(i.unwrap().as_record().is_ok());
+    }
+
+    // schema attributes are validated by the logical type
+    let mut schema =
+        Schema::from_str(r##"{"type": "string", "logicalType": "geo-point"}"##).unwrap();
+    assert!(schema.register_logical_type(GeoPoint).is_err());
+}
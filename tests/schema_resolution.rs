@@ -3,7 +3,7 @@ mod common;
 
 use serde::{Deserialize, Serialize};
 
-use avrow::{from_value, Codec, Reader, Schema, Value};
+use avrow::{from_value, Codec, Decimal, Reader, Schema, Value};
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -152,6 +152,31 @@ fn enum_fails_schema_resolution() {
     }
 }
 
+#[test]
+fn enum_resolves_by_symbol_name_and_default() {
+    let schema =
+        Schema::from_str(r##"{"type": "enum", "name": "Foo", "symbols": ["A", "B", "C", "D"] }"##)
+            .unwrap();
+    let mut writer = writer_from_schema(&schema, Codec::Null);
+    writer.serialize(Foo::B).unwrap();
+    writer.serialize(Foo::C).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "enum", "name": "Foo", "symbols": ["E", "B", "A"], "default": "E"}"##,
+    )
+    .unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf);
+    assert_eq!(
+        reader.next().unwrap().unwrap(),
+        Value::Enum("B".to_string())
+    );
+    assert_eq!(
+        reader.next().unwrap().unwrap(),
+        Value::Enum("E".to_string())
+    );
+}
+
 #[test]
 #[should_panic]
 fn schema_resolution_map() {
@@ -211,7 +236,7 @@ fn record_schema_resolution_with_default_value() {
 #[cfg(feature = "codec")]
 fn writer_is_a_union_but_reader_is_not() {
     let writer_schema = Schema::from_str(r##"["null", "int"]"##).unwrap();
-    let mut writer = writer_from_schema(&writer_schema, Codec::Deflate);
+    let mut writer = writer_from_schema(&writer_schema, Codec::Deflate { level: 6 });
     writer.serialize(()).unwrap();
     writer.serialize(3).unwrap();
 
@@ -232,10 +257,9 @@ fn reader_is_a_union_but_writer_is_not() {
 
     let buf = writer.into_inner().unwrap();
 
-    // err
+    // err, the writer's schema can never be read with the reader's schema
     let reader_schema = Schema::from_str(r##"["null", "string"]"##).unwrap();
-    let mut reader = reader_with_schema(&reader_schema, buf.clone());
-    assert!(reader.next().unwrap().is_err());
+    assert!(Reader::with_schema(buf.as_slice(), &reader_schema).is_err());
 
     // ok
     let reader_schema = Schema::from_str(r##"["null", "int"]"##).unwrap();
@@ -313,3 +337,292 @@ fn null() {
     let mut reader = reader_with_schema(&reader_schema, buf);
     assert!(reader.next().unwrap().is_ok());
 }
+
+#[test]
+fn record_and_field_aliases() {
+    let writer_schema = Schema::from_str(
+        r##"{"type": "record", "name": "Person", "namespace": "org.old", "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["A", "B"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "md5", "size": 2}}
+        ]}"##,
+    )
+    .unwrap();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    let mut rec = avrow::Record::new("org.old.Person");
+    rec.insert("id", 7i64).unwrap();
+    rec.insert("kind", Value::Enum("B".to_string())).unwrap();
+    rec.insert("hash", Value::Fixed(vec![1, 2])).unwrap();
+    writer.write(Value::Record(rec)).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "User", "namespace": "org.new", "aliases": ["org.old.Person"], "fields": [
+            {"name": "key", "aliases": ["id"], "type": "long"},
+            {"name": "kind", "type": {"type": "enum", "name": "org.old.Category", "aliases": ["Kind"], "symbols": ["A", "B"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "digest", "aliases": ["org.old.md5"], "size": 2}}
+        ]}"##,
+    )
+    .unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf.clone());
+    let mut expected = avrow::Record::new("org.new.User");
+    expected.insert("key", 7i64).unwrap();
+    expected
+        .insert("kind", Value::Enum("B".to_string()))
+        .unwrap();
+    expected.insert("hash", Value::Fixed(vec![1, 2])).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), Value::Record(expected));
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "User", "aliases": ["Human"], "fields": [
+            {"name": "key", "type": "long"}
+        ]}"##,
+    )
+    .unwrap();
+    let err = Reader::with_schema(buf.as_slice(), &reader_schema)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("org.old.Person"));
+    assert!(err.contains(r#"["User", "Human"]"#));
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "org.old.Person", "fields": [
+            {"name": "key", "aliases": ["uid"], "type": "long"}
+        ]}"##,
+    )
+    .unwrap();
+    let err = Reader::with_schema(buf.as_slice(), &reader_schema)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains(r#"["key", "uid"]"#));
+}
+
+#[test]
+fn nested_resolution_with_promotion() {
+    // array<int> -> array<long>
+    let writer_schema = Schema::from_str(r##"{"type": "array", "items": "int"}"##).unwrap();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    writer.serialize(vec![1, 2, 3]).unwrap();
+    writer.serialize(vec![4]).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(r##"{"type": "array", "items": "long"}"##).unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf);
+    let longs = |v: &[i64]| Value::Array(v.iter().map(|l| Value::Long(*l)).collect());
+    assert_eq!(reader.next().unwrap().unwrap(), longs(&[1, 2, 3]));
+    assert_eq!(reader.next().unwrap().unwrap(), longs(&[4]));
+    assert!(reader.next().is_none());
+
+    // union branches are resolved recursively and promoted
+    let writer_schema = Schema::from_str(r##"["null", "int"]"##).unwrap();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    writer.serialize(3).unwrap();
+    writer.serialize(()).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(r##"["string", "double", "null"]"##).unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf);
+    assert_eq!(reader.next().unwrap().unwrap(), Value::Double(3.0));
+    assert_eq!(reader.next().unwrap().unwrap(), Value::Null);
+}
+
+#[derive(Serialize)]
+struct IntList {
+    value: i32,
+    next: Option<Box<IntList>>,
+}
+
+#[test]
+fn map_of_evolved_recursive_records() {
+    let writer_schema = Schema::from_str(
+        r##"{"type": "map", "values": {"type": "record", "name": "LongList", "fields": [
+            {"name": "value", "type": "int"},
+            {"name": "next", "type": ["null", "LongList"]}
+        ]}}"##,
+    )
+    .unwrap();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    let mut m = HashMap::new();
+    m.insert(
+        "a",
+        IntList {
+            value: 1,
+            next: Some(Box::new(IntList {
+                value: 2,
+                next: None,
+            })),
+        },
+    );
+    writer.serialize(m).unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "map", "values": {"type": "record", "name": "LongList", "fields": [
+            {"name": "value", "type": "long"},
+            {"name": "next", "type": ["null", "LongList"]},
+            {"name": "other", "type": "long", "default": 7}
+        ]}}"##,
+    )
+    .unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf);
+    let value = reader.next().unwrap().unwrap();
+    let m: HashMap<String, LongListDefault> = from_value(&Ok(value)).unwrap();
+    let list = &m["a"];
+    assert_eq!((list.value, list.other), (1, 7));
+    let next = list.next.as_ref().unwrap();
+    assert_eq!((next.value, next.other), (2, 7));
+    assert!(next.next.is_none());
+}
+
+#[test]
+fn incompatible_schemas_fail_at_construction() {
+    let writer_schema = MockSchema.record();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    writer
+        .serialize(LongList {
+            value: 1,
+            next: None,
+        })
+        .unwrap();
+    let buf = writer.into_inner().unwrap();
+
+    // reader field without a default that is not in the writer's record
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "LongList", "fields": [
+            {"name": "value", "type": "long"},
+            {"name": "size", "type": "int"}
+        ]}"##,
+    )
+    .unwrap();
+    assert!(Reader::with_schema(buf.as_slice(), &reader_schema).is_err());
+
+    // writer's fields not in the reader's record are skipped
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "LongList", "fields": [
+            {"name": "next", "type": ["null", "LongList"]}
+        ]}"##,
+    )
+    .unwrap();
+    let mut reader = reader_with_schema(&reader_schema, buf);
+    let mut expected = avrow::Record::new("LongList");
+    expected.insert("next", Value::Null).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), Value::Record(expected));
+}
+
+#[test]
+fn projection_skips_dropped_fields() {
+    let writer_schema = Schema::from_str(
+        r##"{"type": "record", "name": "Wide", "fields": [
+            {"name": "name", "type": "string"},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+            {"name": "id", "type": "long"},
+            {"name": "attrs", "type": {"type": "map", "values": ["null", "double"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "md5", "size": 4}},
+            {"name": "flag", "type": "boolean"}
+        ]}"##,
+    )
+    .unwrap();
+    let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+    for id in 0..3i64 {
+        let mut rec = avrow::Record::new("Wide");
+        rec.insert("name", "wide record").unwrap();
+        rec.insert("tags", vec!["a", "b"]).unwrap();
+        rec.insert("id", id).unwrap();
+        let mut attrs = HashMap::new();
+        attrs.insert("x".to_string(), Value::Double(1.5));
+        rec.insert("attrs", attrs).unwrap();
+        rec.insert("hash", Value::Fixed(vec![1, 2, 3, 4])).unwrap();
+        rec.insert("flag", true).unwrap();
+        writer.write(Value::Record(rec)).unwrap();
+    }
+    let buf = writer.into_inner().unwrap();
+
+    let reader_schema = Schema::from_str(
+        r##"{"type": "record", "name": "Wide", "fields": [
+            {"name": "flag", "type": "boolean"},
+            {"name": "id", "type": "long"}
+        ]}"##,
+    )
+    .unwrap();
+    let reader = reader_with_schema(&reader_schema, buf);
+    let records: Vec<_> = reader.map(|v| v.unwrap()).collect();
+    let expected: Vec<_> = (0..3i64)
+        .map(|id| {
+            let mut rec = avrow::Record::new("Wide");
+            rec.insert("flag", true).unwrap();
+            rec.insert("id", id).unwrap();
+            Value::Record(rec)
+        })
+        .collect();
+    assert_eq!(records, expected);
+}
+
+#[test]
+fn mismatched_logical_types_resolve_as_underlying() {
+    let resolve = |writer_schema: &str, value: Value, reader_schema: &str| -> Value {
+        let writer_schema = Schema::from_str(writer_schema).unwrap();
+        let mut writer = writer_from_schema(&writer_schema, Codec::Null);
+        writer.write(value).unwrap();
+        let buf = writer.into_inner().unwrap();
+        let reader_schema = Schema::from_str(reader_schema).unwrap();
+        let mut reader = reader_with_schema(&reader_schema, buf);
+        reader.next().unwrap().unwrap()
+    };
+    let decimal = |scale: usize| {
+        format!(
+            r##"{{"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": {}}}"##,
+            scale
+        )
+    };
+    let twelve_34 = || Value::Decimal(Decimal::from_str("12.34").unwrap());
+
+    // decimals with a different scale are read as the unscaled bytes
+    assert_eq!(
+        resolve(&decimal(2), twelve_34(), &decimal(3)),
+        Value::Bytes(vec![0x04, 0xd2])
+    );
+    assert_eq!(resolve(&decimal(2), twelve_34(), &decimal(2)), twelve_34());
+
+    // timestamps of a different precision are read as longs
+    let micros = r##"{"type": "long", "logicalType": "timestamp-micros"}"##;
+    let millis = r##"{"type": "long", "logicalType": "timestamp-millis"}"##;
+    assert_eq!(
+        resolve(micros, Value::TimestampMicros(1_000_000), millis),
+        Value::Long(1_000_000)
+    );
+    assert_eq!(
+        resolve(millis, Value::TimestampMillis(1_000), millis),
+        Value::TimestampMillis(1_000)
+    );
+
+    // logical and plain types are read as the underlying type
+    assert_eq!(
+        resolve(r##""long""##, Value::Long(1_000), millis),
+        Value::Long(1_000)
+    );
+    assert_eq!(
+        resolve(millis, Value::TimestampMillis(1_000), r##""long""##),
+        Value::Long(1_000)
+    );
+    assert_eq!(
+        resolve(r##""bytes""##, Value::Bytes(vec![0x04, 0xd2]), &decimal(2)),
+        Value::Bytes(vec![0x04, 0xd2])
+    );
+    let uuid = r##"{"type": "string", "logicalType": "uuid"}"##;
+    assert_eq!(
+        resolve(r##""string""##, Value::Str("not a uuid".to_string()), uuid),
+        Value::Str("not a uuid".to_string())
+    );
+
+    // a reader's union branch keeps the logical type of a matching writer
+    assert_eq!(
+        resolve(
+            millis,
+            Value::TimestampMillis(1_000),
+            &format!(r##"["null", {}]"##, millis)
+        ),
+        Value::TimestampMillis(1_000)
+    );
+}